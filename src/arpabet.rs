@@ -0,0 +1,148 @@
+declare_static_array!(pub ARPABET, (&'static str, &'static str), [
+    ("AA", "ɑ"),
+    ("AE", "æ"),
+    ("AH", "ʌ"),
+    ("AH0", "ə"),
+    ("AO", "ɔ"),
+    ("AW", "aʊ"),
+    ("AX", "ə"),
+    ("AXR", "ɚ"),
+    ("AY", "aɪ"),
+    ("EH", "ɛ"),
+    ("ER", "ɝ"),
+    ("ER0", "ɚ"),
+    ("EY", "eɪ"),
+    ("IH", "ɪ"),
+    ("IX", "ɨ"),
+    ("IY", "i"),
+    ("OW", "oʊ"),
+    ("OY", "ɔɪ"),
+    ("UH", "ʊ"),
+    ("UW", "u"),
+    ("UX", "ʉ"),
+    ("B", "b"),
+    ("CH", "tʃ"),
+    ("D", "d"),
+    ("DH", "ð"),
+    ("DX", "ɾ"),
+    ("EL", "l̩"),
+    ("EM", "m̩"),
+    ("EN", "n̩"),
+    ("F", "f"),
+    ("G", "ɡ"),
+    ("HH", "h"),
+    ("JH", "dʒ"),
+    ("K", "k"),
+    ("L", "l"),
+    ("M", "m"),
+    ("N", "n"),
+    ("NG", "ŋ"),
+    ("NX", "ɾ̃"),
+    ("P", "p"),
+    ("Q", "ʔ"),
+    ("R", "ɹ"),
+    ("S", "s"),
+    ("SH", "ʃ"),
+    ("T", "t"),
+    ("TH", "θ"),
+    ("V", "v"),
+    ("W", "w"),
+    ("WH", "ʍ"),
+    ("Y", "j"),
+    ("Z", "z"),
+    ("ZH", "ʒ")
+]);
+
+fn arpabet_symbol(symbol: &str) -> Option<&'static str> {
+    ARPABET.iter().find(|s| s.0 == symbol).map(|s| s.1)
+}
+
+pub fn arpabet_to_ipa(phonemes: &str) -> Option<String> {
+    let mut res = String::with_capacity(phonemes.len());
+    for phoneme in phonemes.split_whitespace() {
+        let phoneme = phoneme.to_uppercase();
+        let (symbol, stress) = match phoneme.strip_suffix(['0', '1', '2']) {
+            Some(symbol) => (symbol, phoneme.chars().last()),
+            None => (&phoneme[..], None),
+        };
+        match stress {
+            Some('1') => res.push('ˈ'),
+            Some('2') => res.push('ˌ'),
+            _ => (),
+        }
+        let ipa = match stress {
+            Some('0') => arpabet_symbol(&phoneme),
+            _ => None,
+        }
+        .or_else(|| arpabet_symbol(symbol))?;
+        res.push_str(ipa);
+    }
+    Some(res)
+}
+
+pub fn to_cmudict_pair(line: &str) -> Result<Option<(&str, String)>, String> {
+    let line = line.split(" #").next().unwrap_or("").trim();
+    if line.is_empty() || line.starts_with(";;;") {
+        return Ok(None);
+    }
+    let (word, phonemes) = line
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("Could not parse line {:?}", line))?;
+    let word = match word.strip_suffix(')') {
+        Some(w) => w
+            .rsplit_once('(')
+            .filter(|v| v.1.chars().all(|c| c.is_ascii_digit()))
+            .map_or(word, |v| v.0),
+        None => word,
+    };
+    let phonemes = phonemes.trim();
+    match arpabet_to_ipa(phonemes) {
+        Some(ipa) => Ok(Some((word, ipa))),
+        None => Err(format!("Could not parse ARPAbet {:?}", phonemes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("", Some(""))]
+    #[case("W ER1 D", Some("wˈɝd"))]
+    #[case("w er0 d", Some("wɚd"))]
+    #[case("AH0 B R IY2 V IY0 EY1 SH AH0 N Z", Some("əbɹˌiviˈeɪʃənz"))]
+    #[case("T AH1 CH", Some("tˈʌtʃ"))]
+    #[case("T XX1", None)]
+    #[case("T AH3", None)]
+    fn test_arpabet_to_ipa(
+        #[case] input: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        assert_eq!(expected.map(String::from), arpabet_to_ipa(input));
+    }
+
+    #[rstest]
+    #[case("", Ok(None))]
+    #[case(";;; comment", Ok(None))]
+    #[case("WORD", Err("Could not parse line \"WORD\""))]
+    #[case("WORD  W XX1 D", Err("Could not parse ARPAbet \"W XX1 D\""))]
+    #[case("WORD  W ER1 D", Ok(Some(("WORD", "wˈɝd"))))]
+    #[case("word w er1 d", Ok(Some(("word", "wˈɝd"))))]
+    #[case("WORD(2)  W ER1 D", Ok(Some(("WORD", "wˈɝd"))))]
+    #[case("(PAREN  P ER0 EH1 N", Ok(Some(("(PAREN", "pɚˈɛn"))))]
+    #[case("X(Y)  EH1 K S", Ok(Some(("X(Y)", "ˈɛks"))))]
+    #[case("#HASH  HH AE1 SH", Ok(Some(("#HASH", "hˈæʃ"))))]
+    #[case("d'artagnan D AH0 T AE1 N Y AH0 N # foreign", Ok(Some(("d'artagnan", "dətˈænjən"))))]
+    fn test_to_cmudict_pair(
+        #[case] input: &str,
+        #[case] expected: Result<Option<(&str, &str)>, &str>,
+    ) {
+        assert_eq!(
+            expected
+                .map(|p| p.map(|p| (p.0, String::from(p.1))))
+                .map_err(String::from),
+            to_cmudict_pair(input)
+        );
+    }
+}
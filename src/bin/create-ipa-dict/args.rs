@@ -12,6 +12,7 @@ pub struct Args {
     pub voice: Option<String>,
     pub list_languages: bool,
     pub ascii: bool,
+    pub strict: bool,
 }
 
 impl Args {
@@ -23,6 +24,7 @@ impl Args {
             voice: None,
             list_languages: false,
             ascii: false,
+            strict: false,
         }
     }
 }
@@ -61,6 +63,11 @@ impl ArgParser for Args {
                 StoreTrue,
                 "Use espeak's ascii phoneme names",
             );
+            parser.refer(&mut opts.strict).add_option(
+                &["-s", "--strict"],
+                StoreTrue,
+                "Fail on malformed input lines",
+            );
             parser.refer(&mut opts.input_filename).add_argument(
                 "input",
                 StoreOption,
@@ -90,8 +97,9 @@ mod tests {
     #[case(&["cmd", "-h"], Err(0))]
     #[case(&["cmd", "-i", "yy", "-l"], Err(2))]
     #[case(&["cmd"], Ok(Args::new()))]
-    #[case(&["cmd", "-L"], Ok(Args {input_filename: None, output_filename: None, language: None, voice: None, list_languages: true, ascii: false}))]
-    #[case(&["cmd", "-o", "xx", "-v", "vv", "-l", "zz", "-a", "yy"], Ok(Args {input_filename: Some(String::from("yy")), output_filename: Some(String::from("xx")), language: Some(String::from("zz")), voice: Some(String::from("vv")), list_languages: false, ascii: true}))]
+    #[case(&["cmd", "-L"], Ok(Args {input_filename: None, output_filename: None, language: None, voice: None, list_languages: true, ascii: false, strict: false}))]
+    #[case(&["cmd", "-o", "xx", "-v", "vv", "-l", "zz", "-a", "yy"], Ok(Args {input_filename: Some(String::from("yy")), output_filename: Some(String::from("xx")), language: Some(String::from("zz")), voice: Some(String::from("vv")), list_languages: false, ascii: true, strict: false}))]
+    #[case(&["cmd", "-s"], Ok(Args {input_filename: None, output_filename: None, language: None, voice: None, list_languages: false, ascii: false, strict: true}))]
     fn test_args_parse(
         #[case] args: &[&str],
         #[case] expected: Result<Args, i32>,
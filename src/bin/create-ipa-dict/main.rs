@@ -1,12 +1,12 @@
 use std::error::Error;
 use std::io::Write;
 
-use streaming_iterator::StreamingIterator;
-
 use args::Args;
 use options::Options;
 
+use find_similar_words::dictionary::LANGUAGE_HEADER;
 use find_similar_words::language::Languages;
+use find_similar_words::parse_error::{ParseLog, ParseMode};
 use find_similar_words::phoneme::TextToPhonemes;
 use find_similar_words::util::ArgParser;
 
@@ -42,21 +42,41 @@ fn process_input(args: &Args) -> Result<(), Box<dyn Error>> {
     speaker.set_voice_raw(&opts.voice)?;
     let mut speaker = TextToPhonemes::new(&*speaker, opts.ascii);
 
-    let mut lines = opts.input.iter();
-    while let Some(line) = lines.next() {
-        let line = line.trim();
-        if !line.is_empty() {
-            let phonemes = speaker.text_to_phonemes(line);
-            if phonemes.is_empty() {
-                eprintln!("Warning: no phonemes found for {:?}", line);
-            } else {
-                writeln!(opts.output, "{}\t{}", line, phonemes)?;
-                //output.flush()?;
-            }
-        }
+    if let Some(language) = opts.language.as_ref() {
+        writeln!(opts.output, "# {}: {}", LANGUAGE_HEADER, language)?;
     }
 
-    opts.output.flush()?;
+    let mode = if args.strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    };
+    let mut log = ParseLog::new(mode, args.input_filename.as_deref());
+    let output = &mut opts.output;
+    opts.input.for_each_line(
+        |line_number, line| -> Result<(), Box<dyn Error>> {
+            let line = match line {
+                Ok(line) => line.trim(),
+                Err(e) => return Ok(log.report(line_number, &e.to_string())?),
+            };
+            if !line.is_empty() {
+                let phonemes = speaker.text_to_phonemes(line);
+                if phonemes.is_empty() {
+                    let reason = format!("No phonemes found for {:?}", line);
+                    log.report(line_number, &reason)?;
+                } else {
+                    writeln!(output, "{}\t{}", line, phonemes)?;
+                    //output.flush()?;
+                }
+            }
+            Ok(())
+        },
+    )?;
+
+    opts.output.finish()?;
+    for warning in log.warnings() {
+        eprintln!("Warning: {}", warning);
+    }
     Ok(())
 }
 
@@ -2,20 +2,28 @@ use std::error::Error;
 use std::fmt::{self, Display, Formatter};
 use std::io;
 
-use streaming_iterator::{Chain, StreamingIterator};
+use streaming_iterator::StreamingIterator;
 
 use crate::args::Args;
 
-use find_similar_words::iter::{lines, str_lines, Lines, StrLines};
+use find_similar_words::iter::{lines, str_lines};
 use find_similar_words::language::Languages;
 use find_similar_words::language_detection::detect_file_language;
-use find_similar_words::util::{open_input_file, open_output_file};
+use find_similar_words::util::{open_input_file, open_output_file, Encoder};
 
 #[derive(Debug)]
 pub struct NoVoiceForLanguage {
     language: String,
 }
 
+enum Selection {
+    Language {
+        language: String,
+        voice: Option<String>,
+    },
+    Voice(String),
+}
+
 pub struct Input {
     file: Box<dyn io::BufRead>,
     start: String,
@@ -23,8 +31,9 @@ pub struct Input {
 
 pub struct Options {
     pub input: Input,
-    pub output: Box<dyn io::Write>,
+    pub output: Encoder<Box<dyn io::Write>>,
     pub voice: String,
+    pub language: Option<String>,
     pub ascii: bool,
 }
 
@@ -48,31 +57,53 @@ impl Display for NoVoiceForLanguage {
 impl Error for NoVoiceForLanguage {}
 
 impl Input {
-    fn get_espeak_voice(
-        &mut self,
-        args: &Args,
-    ) -> Result<String, Box<dyn Error>> {
+    fn get_selection(&mut self, args: &Args) -> io::Result<Selection> {
+        match (args.language.as_ref(), args.voice.as_ref()) {
+            (Some(language), voice) => Ok(Selection::Language {
+                language: language.clone(),
+                voice: voice.cloned(),
+            }),
+            (None, Some(voice)) => Ok(Selection::Voice(voice.clone())),
+            (None, None) => {
+                eprintln!("Detecting language...");
+                let detected = detect_file_language(&mut self.file)?;
+                self.start = detected.partial_file_contents;
+                eprintln!("Detected language {}", detected.language);
+                Ok(Selection::Language {
+                    language: String::from(detected.language),
+                    voice: None,
+                })
+            },
+        }
+    }
+
+    fn get_language_and_voice(
+        selection: Selection,
+    ) -> Result<(Option<String>, String), Box<dyn Error>> {
         let languages = Languages::get_supported();
-        match args.voice.as_ref() {
-            Some(voice) => Ok(voice.clone()),
-            None => {
-                let lang: &str = match args.language.as_ref() {
-                    Some(l) => l,
+        match selection {
+            Selection::Language {
+                language,
+                voice: Some(voice),
+            } => Ok((Some(language), voice)),
+            Selection::Language {
+                language,
+                voice: None,
+            } => {
+                match languages
+                    .get(&language)
+                    .and_then(|l| l.default_espeak_voice())
+                {
+                    Some(v) => Ok((Some(language), v.filename().clone())),
                     None => {
-                        eprintln!("Detecting language...");
-                        let detected = detect_file_language(&mut self.file)?;
-                        self.start = detected.partial_file_contents;
-                        eprintln!("Detected language {}", detected.language);
-                        detected.language
+                        Err(Box::new(NoVoiceForLanguage::from(&language[..])))
                     },
-                };
-
-                match languages.get(lang).and_then(|l| l.default_espeak_voice())
-                {
-                    Some(v) => Ok(v.filename().clone()),
-                    None => Err(Box::new(NoVoiceForLanguage::from(lang))),
                 }
             },
+            Selection::Voice(voice) => {
+                let language = languages.get_by_voice(&voice);
+                Ok((language.map(|l| l.name().clone()), voice))
+            },
         }
     }
 
@@ -84,8 +115,25 @@ impl Input {
         })
     }
 
-    pub fn iter(&mut self) -> Chain<StrLines, Lines<Box<dyn io::BufRead>>> {
-        str_lines(&self.start).chain(lines(&mut self.file))
+    pub fn for_each_line<F, E>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(usize, Result<&str, io::Error>) -> Result<(), E>,
+    {
+        let mut line_number = 0;
+        let mut start = str_lines(&self.start);
+        while let Some(line) = start.next() {
+            line_number += 1;
+            f(line_number, Ok(line))?;
+        }
+        let mut lines = lines(&mut self.file);
+        while let Some(line) = lines.next() {
+            line_number += 1;
+            f(line_number, Ok(line))?;
+        }
+        match lines.take_error() {
+            Some(e) => f(line_number + 1, Err(e)),
+            None => Ok(()),
+        }
     }
 }
 
@@ -93,11 +141,13 @@ impl Options {
     pub fn from_args(args: &Args) -> Result<Self, Box<dyn Error>> {
         let mut input = Input::from_args(args)?;
         let output = open_output_file(&args.output_filename)?;
-        let voice = input.get_espeak_voice(args)?;
+        let selection = input.get_selection(args)?;
+        let (language, voice) = Input::get_language_and_voice(selection)?;
         Ok(Self {
             input,
             output,
             voice,
+            language,
             ascii: args.ascii,
         })
     }
@@ -108,13 +158,12 @@ mod tests {
     use super::*;
     use rstest::*;
     use std::io::Cursor;
-    use streaming_iterator::StreamingIterator;
 
     #[rstest]
     #[case("", "", &[])]
     #[case("", "xx\nyy\n", &["xx", "yy"])]
     #[case("xx\nyy", "1\n2\n3\n", &["xx", "yy", "1", "2", "3"])]
-    fn test_input_iter(
+    fn test_input_for_each_line(
         #[case] start: &str,
         #[case] file_contents: &str,
         #[case] expected: &[&str],
@@ -123,7 +172,14 @@ mod tests {
             start: String::from(start),
             file: Box::new(Cursor::new(String::from(file_contents))),
         };
-        let res: Vec<String> = input.iter().owned().collect();
+        let mut res = Vec::new();
+        input
+            .for_each_line(|line_number, line| {
+                res.push(String::from(line?));
+                assert_eq!(res.len(), line_number);
+                Ok::<(), io::Error>(())
+            })
+            .unwrap();
         assert_eq!(expected, res);
     }
 }
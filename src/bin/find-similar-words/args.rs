@@ -1,26 +1,81 @@
 use std::io::Write;
 
-use argparse::{ArgumentParser, Collect, Store, StoreTrue};
+use argparse::{
+    ArgumentParser, Collect, Store, StoreConst, StoreOption, StoreTrue,
+};
 
+use find_similar_words::dictionary::IndexType;
+use find_similar_words::dictionary_format::DictionaryFormat;
+use find_similar_words::distance::Metric;
+use find_similar_words::minimal_pairs::Contrast;
+use find_similar_words::output::OutputFormat;
+use find_similar_words::syllable::WordPart;
 use find_similar_words::util::ArgParser;
+use find_similar_words::word_groups::Grouping;
 
 #[derive(Debug, PartialEq)]
 pub struct Args {
     pub input_filenames: Vec<String>,
+    pub output_filename: Option<String>,
+    pub output_format: OutputFormat,
+    pub format: DictionaryFormat,
+    pub strict: bool,
     pub normalize: bool,
-    pub max_distance: usize,
+    pub normalize_profile: Option<String>,
+    pub part: Option<WordPart>,
+    pub metric: Metric,
+    pub max_distance: f64,
+    pub top: usize,
+    pub grouping: Option<Grouping>,
+    pub distances: bool,
+    pub alignments: bool,
     pub min_word_length: usize,
     pub max_word_length: usize,
+    pub min_frequency: u64,
+    pub pos: Option<String>,
+    pub sort_by_frequency: bool,
+    pub index: IndexType,
+    pub cache: Option<String>,
+    pub jobs: usize,
+    pub min_sources: usize,
+    pub required_sources: Vec<String>,
+    pub false_friends: bool,
+    pub min_spelling_distance: usize,
+    pub minimal_pairs: bool,
+    pub contrasts: Vec<Contrast>,
 }
 
 impl Args {
     pub fn new() -> Self {
         Self {
             input_filenames: Vec::new(),
+            output_filename: None,
+            output_format: OutputFormat::Text,
+            format: DictionaryFormat::Auto,
+            strict: false,
             normalize: false,
-            max_distance: 0,
+            normalize_profile: None,
+            part: None,
+            metric: Metric::Levenshtein,
+            max_distance: 0.0,
+            top: 0,
+            grouping: None,
+            distances: false,
+            alignments: false,
             min_word_length: 0,
             max_word_length: usize::MAX,
+            min_frequency: 0,
+            pos: None,
+            sort_by_frequency: false,
+            index: IndexType::None,
+            cache: None,
+            jobs: 1,
+            min_sources: 0,
+            required_sources: Vec::new(),
+            false_friends: false,
+            min_spelling_distance: 0,
+            minimal_pairs: false,
+            contrasts: Vec::new(),
         }
     }
 }
@@ -35,11 +90,47 @@ impl ArgParser for Args {
         {
             let mut parser = ArgumentParser::new();
             parser.set_description("Find words with similar pronunciations.");
+            parser.refer(&mut opts.format).add_option(
+                &["-f", "--format"],
+                Store,
+                "Set dictionary format (auto, tsv, cmudict, ipa-dict, wikipron) (default: auto)",
+            );
+            parser.refer(&mut opts.strict).add_option(
+                &["-s", "--strict"],
+                StoreTrue,
+                "Fail on malformed input lines",
+            );
             parser.refer(&mut opts.normalize).add_option(
                 &["-n", "--normalize"],
                 StoreTrue,
                 "Normalize word transcriptions",
             );
+            parser
+                .refer(&mut opts.normalize_profile)
+                .metavar("PROFILE")
+                .add_option(
+                    &["-N", "--normalize-profile"],
+                    StoreOption,
+                    "Normalize word transcriptions using profile (strict, default, aggressive or FILE)",
+                );
+            parser
+                .refer(&mut opts.part)
+                .metavar("PART")
+                .add_option(
+                    &["-P", "--part"],
+                    StoreOption,
+                    "Only compare PART of word transcriptions (rhyme, onset, stressed-syllable)",
+                )
+                .add_option(
+                    &["-r", "--rhymes"],
+                    StoreConst(Some(WordPart::Rhyme)),
+                    "Only compare last stressed vowels and following sounds",
+                )
+                .add_option(
+                    &["-a", "--alliteration"],
+                    StoreConst(Some(WordPart::Onset)),
+                    "Only compare initial consonant clusters",
+                );
             parser.refer(&mut opts.min_word_length).add_option(
                 &["-l", "--min-length"],
                 Store,
@@ -50,22 +141,147 @@ impl ArgParser for Args {
                 Store,
                 "Set maximum word length (default: none)",
             );
+            parser
+                .refer(&mut opts.min_frequency)
+                .metavar("N")
+                .add_option(
+                    &["--min-frequency"],
+                    Store,
+                    "Set minimum word frequency (default: none)",
+                );
+            parser.refer(&mut opts.pos).metavar("TAG").add_option(
+                &["-p", "--pos"],
+                StoreOption,
+                "Only use words with part-of-speech tag TAG",
+            );
+            parser.refer(&mut opts.sort_by_frequency).add_option(
+                &["-F", "--sort-by-frequency"],
+                StoreTrue,
+                "Sort results by word frequency",
+            );
+            parser.refer(&mut opts.metric).add_option(
+                &["-m", "--metric"],
+                Store,
+                "Set distance metric (levenshtein, features) (default: levenshtein)",
+            );
             parser.refer(&mut opts.max_distance).add_option(
                 &["-d", "--max-distance"],
                 Store,
-                "Set max levenshtein distance between word transcriptions (default: 0)",
+                "Set max distance between word transcriptions (default: 0)",
+            );
+            parser.refer(&mut opts.top).metavar("K").add_option(
+                &["-k", "--top"],
+                Store,
+                "Find K nearest words for each word (default: none)",
+            );
+            parser
+                .refer(&mut opts.grouping)
+                .metavar("GROUPING")
+                .add_option(
+                    &["-g", "--grouping"],
+                    StoreOption,
+                    "Group results (connected, complete, star, diameter:D) (default: none)",
+                );
+            parser.refer(&mut opts.distances).add_option(
+                &["-D", "--distances"],
+                StoreTrue,
+                "Print distances between matching words",
+            );
+            parser.refer(&mut opts.alignments).add_option(
+                &["-A", "--alignments"],
+                StoreTrue,
+                "Print distances and alignments between matching words",
+            );
+            parser.refer(&mut opts.index).metavar("TYPE").add_option(
+                &["-i", "--index"],
+                Store,
+                "Set search index type (none, bk-tree, deletions) (default: none) (deletions is meant for levenshtein distances up to 2)",
+            );
+            parser.refer(&mut opts.cache).metavar("FILE").add_option(
+                &["-c", "--cache"],
+                StoreOption,
+                "Load dictionaries from cache FILE (created if missing or outdated)",
+            );
+            parser.refer(&mut opts.jobs).metavar("N").add_option(
+                &["-j", "--jobs"],
+                Store,
+                "Set number of search threads (0: number of CPUs) (default: 1)",
+            );
+            parser
+                .refer(&mut opts.output_filename)
+                .metavar("FILE")
+                .add_option(
+                    &["-o", "--output"],
+                    StoreOption,
+                    "Set output file (default: stdout)",
+                );
+            parser
+                .refer(&mut opts.output_format)
+                .metavar("FORMAT")
+                .add_option(
+                    &["-O", "--output-format"],
+                    Store,
+                    "Set output format (text, jsonl, csv, tsv, dot, graphml) (default: text)",
+                );
+            parser.refer(&mut opts.min_sources).metavar("K").add_option(
+                &["-S", "--min-sources"],
+                Store,
+                "Only print groups with words from at least K files (default: 2 if multiple files)",
+            );
+            parser
+                .refer(&mut opts.required_sources)
+                .metavar("FILE")
+                .add_option(
+                    &["-R", "--require-source"],
+                    Collect,
+                    "Only print groups with words from FILE",
+                );
+            parser.refer(&mut opts.false_friends).add_option(
+                &["-x", "--false-friends"],
+                StoreTrue,
+                "Print similar words from different files with their transcriptions",
             );
+            parser
+                .refer(&mut opts.min_spelling_distance)
+                .metavar("N")
+                .add_option(
+                    &["--min-spelling-distance"],
+                    Store,
+                    "Only print false friends with spelling distance of at least N (default: 0)",
+                );
+            parser.refer(&mut opts.minimal_pairs).add_option(
+                &["-M", "--minimal-pairs"],
+                StoreTrue,
+                "Print minimal pairs grouped by contrast",
+            );
+            parser
+                .refer(&mut opts.contrasts)
+                .metavar("CONTRAST")
+                .add_option(
+                    &["--contrast"],
+                    Collect,
+                    "Only print minimal pairs with CONTRAST (e.g. l~r)",
+                );
             parser.refer(&mut opts.input_filenames).add_argument(
                 "file",
                 Collect,
-                "Dictionary files (tsv) (max: 2) (default: stdin)",
+                "Dictionary files (tsv) (default: stdin)",
             );
             parser.parse(args, stdout, stderr)?;
         }
-        if opts.input_filenames.len() > 2 {
-            writeln!(stderr, "Too many file arguments").map_err(|_| 2)?;
+        if let Some(source) = opts
+            .required_sources
+            .iter()
+            .find(|s| !opts.input_filenames.contains(s))
+        {
+            writeln!(stderr, "Unknown source {:?}", source).map_err(|_| 2)?;
+            Err(2)
+        } else if opts.false_friends && opts.input_filenames.len() < 2 {
+            writeln!(stderr, "False friends require at least 2 files")
+                .map_err(|_| 2)?;
             Err(2)
         } else {
+            opts.normalize |= opts.normalize_profile.is_some();
             Ok(opts)
         }
     }
@@ -80,9 +296,38 @@ mod tests {
     #[rstest]
     #[case(&["cmd", "-h"], Err(0))]
     #[case(&["cmd", "-L", "-l"], Err(2))]
-    #[case(&["cmd", "xx", "yy", "zz"], Err(2))]
+    #[case(&["cmd", "-R", "xx", "yy"], Err(2))]
+    #[case(&["cmd", "-x", "xx"], Err(2))]
     #[case(&["cmd"], Ok(Args::new()))]
-    #[case(&["cmd", "-n", "-l", "1", "-d", "3", "-L", "2", "xx", "yy"], Ok(Args {input_filenames: vec![String::from("xx"), String::from("yy")], normalize: true, max_distance: 3, min_word_length: 1, max_word_length: 2}))]
+    #[case(&["cmd", "-n", "-l", "1", "-d", "3", "-L", "2", "xx", "yy"], Ok(Args {input_filenames: vec![String::from("xx"), String::from("yy")], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: true, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 3.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 1, max_word_length: 2, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-i", "xx"], Err(2))]
+    #[case(&["cmd", "-m", "xx"], Err(2))]
+    #[case(&["cmd", "-m", "features", "-d", "0.5"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Features, max_distance: 0.5, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-d", "1", "-i", "bk-tree"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 1.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::BkTree, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-d", "1", "-j", "4"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 1.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 4, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-N", "aggressive"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: true, normalize_profile: Some(String::from("aggressive")), part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-k", "5", "-d", "2"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 2.0, top: 5, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-D", "-A", "-d", "1"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 1.0, top: 0, grouping: None, distances: true, alignments: true, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-f", "xx"], Err(2))]
+    #[case(&["cmd", "-f", "cmudict"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::CmuDict, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-s"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: true, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "--min-frequency", "10", "-p", "NOUN", "-F"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 10, pos: Some(String::from("NOUN")), sort_by_frequency: true, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-c", "xx"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: Some(String::from("xx")), jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-o", "xx.gz"], Ok(Args {input_filenames: vec![], output_filename: Some(String::from("xx.gz")), output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-O", "xx"], Err(2))]
+    #[case(&["cmd", "-O", "jsonl"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::JsonLines, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-O", "csv"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Csv, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-g", "xx"], Err(2))]
+    #[case(&["cmd", "-d", "1", "-g", "diameter:2"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 1.0, top: 0, grouping: Some(Grouping::Diameter(2.0)), distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-S", "3", "-R", "zz", "xx", "yy", "zz"], Ok(Args {input_filenames: vec![String::from("xx"), String::from("yy"), String::from("zz")], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 3, required_sources: vec![String::from("zz")], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-x", "--min-spelling-distance", "2", "-d", "1", "xx", "yy"], Ok(Args {input_filenames: vec![String::from("xx"), String::from("yy")], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 1.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: true, min_spelling_distance: 2, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-r", "-d", "1"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: Some(WordPart::Rhyme), metric: Metric::Levenshtein, max_distance: 1.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-a"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: Some(WordPart::Onset), metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-P", "stressed-syllable"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: Some(WordPart::StressedSyllable), metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-a", "-P", "rhyme"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: Some(WordPart::Rhyme), metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: false, contrasts: vec![]}))]
+    #[case(&["cmd", "-P", "xx"], Err(2))]
+    #[case(&["cmd", "-M", "--contrast", "r~l", "--contrast", "p~b"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, format: DictionaryFormat::Auto, strict: false, normalize: false, normalize_profile: None, part: None, metric: Metric::Levenshtein, max_distance: 0.0, top: 0, grouping: None, distances: false, alignments: false, min_word_length: 0, max_word_length: usize::MAX, min_frequency: 0, pos: None, sort_by_frequency: false, index: IndexType::None, cache: None, jobs: 1, min_sources: 0, required_sources: vec![], false_friends: false, min_spelling_distance: 0, minimal_pairs: true, contrasts: vec![Contrast::new("l", "r"), Contrast::new("b", "p")]}))]
+    #[case(&["cmd", "-M", "--contrast", "r"], Err(2))]
     fn test_args_parse(
         #[case] args: &[&str],
         #[case] expected: Result<Args, i32>,
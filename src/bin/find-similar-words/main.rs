@@ -1,46 +1,363 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt::Display;
+use std::io::{self, Write};
+use std::iter;
+use std::ptr;
+use std::thread;
 
 use args::Args;
-use options::Options;
+use options::{Options, Output};
 
 use find_similar_words::dictionary::{Dictionary, Word};
-use find_similar_words::util::ArgParser;
-use find_similar_words::word_groups::WordGroups;
+use find_similar_words::distance::Metric;
+use find_similar_words::false_friends::false_friends;
+use find_similar_words::graph::SimilarityGraph;
+use find_similar_words::minimal_pairs::{
+    group_by_contrast, minimal_pairs, Contrast,
+};
+use find_similar_words::output::{
+    write_csv_pair, write_csv_row, write_jsonl_contrast, write_jsonl_group,
+    OutputFormat, FALSE_FRIEND_HEADER, MINIMAL_PAIR_HEADER, PAIR_HEADER,
+};
+use find_similar_words::util::{open_output_file, ArgParser};
+use find_similar_words::word_groups::{group_words, WordGroups};
 
 mod args;
 mod options;
 
-fn print_result<I: IntoIterator>(words: I)
+const SEARCH_BATCH_SIZE: usize = 1024;
+
+fn print_result<W: Write, I: IntoIterator>(
+    out: &mut W,
+    words: I,
+) -> io::Result<()>
 where
     I::Item: Display,
 {
     let mut first = true;
     for word in words {
         if first {
-            print!("{}", word);
+            write!(out, "{}", word)?;
             first = false;
         } else {
-            print!(" {}", word);
+            write!(out, " {}", word)?;
         }
     }
-    println!();
+    writeln!(out)
+}
+
+fn matching_variant<'a>(
+    variants: &[&'a Word],
+    word: &Word,
+    distance: f64,
+    metric: Metric,
+) -> &'a Word {
+    variants
+        .iter()
+        .find(|v| metric.distance(&v.segments, &word.segments) == distance)
+        .copied()
+        .unwrap_or(variants[0])
+}
+
+fn print_matches<W: Write>(
+    out: &mut W,
+    variants: &[&Word],
+    matches: &[(&Word, f64, usize)],
+    metric: Metric,
+    output: Output,
+) -> io::Result<()> {
+    let word = variants[0];
+    match output {
+        Output::Words => print_result(
+            out,
+            iter::once(word).chain(matches.iter().map(|m| m.0)),
+        ),
+        Output::Distances => print_result(
+            out,
+            iter::once(word.to_string())
+                .chain(matches.iter().map(|(w, d, _)| format!("{}:{}", w, d))),
+        ),
+        Output::Alignments => {
+            for (w, d, _) in matches {
+                let variant = matching_variant(variants, w, *d, metric);
+                write!(out, "{}\t{}\t{}\t", word, w, d)?;
+                print_result(
+                    out,
+                    metric.align(&variant.segments, &w.segments),
+                )?;
+            }
+            Ok(())
+        },
+    }
+}
+
+fn print_group<W: Write>(
+    out: &mut W,
+    graph: &mut SimilarityGraph,
+    opts: &Options,
+    (variants, source): (&[&Word], usize),
+    matches: &[(&Word, f64, usize)],
+) -> io::Result<()> {
+    let source_name = move |i: usize| &opts.sources[i][..];
+    match opts.output_format {
+        OutputFormat::Text if opts.grouping.is_none() => {
+            print_matches(out, variants, matches, opts.metric, opts.output)
+        },
+        OutputFormat::JsonLines => {
+            let mut words: Vec<(&Word, &str)> = Vec::new();
+            for (w, d, _) in matches {
+                let variant = matching_variant(variants, w, *d, opts.metric);
+                if !words.iter().any(|v| ptr::eq(v.0, variant)) {
+                    words.push((variant, source_name(source)));
+                }
+            }
+            if words.is_empty() {
+                words.push((variants[0], source_name(source)));
+            }
+            words.extend(matches.iter().map(|m| (m.0, source_name(m.2))));
+            write_jsonl_group(out, &words, opts.metric)
+        },
+        OutputFormat::Csv | OutputFormat::Tsv => {
+            for (w, d, i) in matches {
+                let variant = matching_variant(variants, w, *d, opts.metric);
+                write_csv_pair(
+                    out,
+                    (variant, source_name(source)),
+                    (*w, source_name(*i)),
+                    *d,
+                    opts.output_format.delimiter(),
+                )?;
+            }
+            Ok(())
+        },
+        OutputFormat::Text | OutputFormat::Dot | OutputFormat::GraphMl => {
+            for (w, d, _) in matches {
+                graph.add_edge(&variants[0].word, &w.word, Some(*d));
+            }
+            Ok(())
+        },
+    }
+}
+
+fn nearest<'a>(
+    dict: &'a Dictionary,
+    variants: &[&Word],
+    metric: Metric,
+    max_distance: f64,
+    k: usize,
+) -> Vec<(&'a Word, f64)> {
+    let mut words = dict.nearest_variants_by(variants, metric, k);
+    if max_distance > 0.0 {
+        words.retain(|w| w.1 <= max_distance);
+    }
+    words
 }
 
-fn search(dict: &Dictionary, dict2: &Dictionary, max_distance: usize) -> usize {
-    let mut words = Vec::<&Word>::new();
+fn search_batch<'a, W, T, F>(words: &'a [W], jobs: usize, search: F) -> Vec<T>
+where
+    W: Sync,
+    T: Send,
+    F: Fn(&'a W) -> T + Sync,
+{
+    if jobs <= 1 {
+        return words.iter().map(search).collect();
+    }
+    let chunk_size = words.len().div_ceil(jobs).max(1);
+    let search = &search;
+    thread::scope(|s| {
+        let handles: Vec<_> = words
+            .chunks(chunk_size)
+            .map(|chunk| {
+                s.spawn(move || chunk.iter().map(search).collect::<Vec<T>>())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    })
+}
+
+fn search<'a, W, F>(
+    out: &mut W,
+    graph: &mut SimilarityGraph,
+    opts: &'a Options,
+    find: F,
+) -> io::Result<usize>
+where
+    W: Write,
+    F: Fn(&'a Dictionary, &[&'a Word]) -> Vec<(&'a Word, f64)> + Sync,
+{
     let mut res = 0_usize;
-    for word in dict.iter() {
-        let similar = dict2
-            .find_similar(word, max_distance)
-            .filter(|w| *w != word);
-        words.push(word);
-        words.extend(similar);
-        if words.len() > 1 {
-            res += 1;
-            print_result(words.iter());
+    let find_all = |source: usize, variants: &[&'a Word]| {
+        let targets = match opts.dicts.len() {
+            1 => 0..1,
+            n => source + 1..n,
+        };
+        let mut matches: Vec<(&Word, f64, usize)> = targets
+            .flat_map(|i| {
+                find(&opts.dicts[i], variants)
+                    .into_iter()
+                    .map(move |(w, d)| (w, d, i))
+            })
+            .collect();
+        if opts.top > 0 {
+            matches.sort_by(|m, m1| m.1.total_cmp(&m1.1));
+            matches.truncate(opts.top);
+        }
+        let sources = iter::once(source).chain(matches.iter().map(|m| m.2));
+        if !opts.filter.matches(sources) {
+            matches.clear();
+        }
+        matches
+    };
+    for (source, dict) in opts.dicts.iter().enumerate() {
+        let mut headwords: Vec<Vec<&Word>> = dict.headwords().collect();
+        if opts.sort_by_frequency {
+            headwords.sort_by_key(|v| {
+                Reverse(v.iter().filter_map(|w| w.frequency).max())
+            });
+        }
+        for batch in headwords.chunks(SEARCH_BATCH_SIZE * opts.jobs) {
+            let results =
+                search_batch(batch, opts.jobs, |v| find_all(source, v));
+            for (variants, matches) in batch.iter().zip(results) {
+                if !matches.is_empty() {
+                    res += 1;
+                    print_group(
+                        out,
+                        graph,
+                        opts,
+                        (&variants[..], source),
+                        &matches,
+                    )?;
+                }
+            }
+        }
+    }
+    Ok(res)
+}
+
+fn print_false_friends<W: Write>(
+    out: &mut W,
+    graph: &mut SimilarityGraph,
+    opts: &Options,
+    min_spelling_distance: usize,
+) -> io::Result<usize> {
+    let dicts: Vec<&Dictionary> = opts.dicts.iter().collect();
+    let results = false_friends(
+        &dicts,
+        opts.metric,
+        opts.max_distance,
+        min_spelling_distance,
+        &opts.filter,
+    );
+    for f in results.iter() {
+        let source = &opts.sources[f.source][..];
+        let source2 = &opts.sources[f.source2][..];
+        match opts.output_format {
+            OutputFormat::Text => writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}\t{}",
+                f.word,
+                f.word.phonemes,
+                f.word2,
+                f.word2.phonemes,
+                f.distance,
+                f.spelling_distance
+            )?,
+            OutputFormat::JsonLines => write_jsonl_group(
+                out,
+                &[(f.word, source), (f.word2, source2)],
+                opts.metric,
+            )?,
+            OutputFormat::Csv | OutputFormat::Tsv => write_csv_row(
+                out,
+                &[
+                    &f.word.word[..],
+                    &f.word.phonemes[..],
+                    source,
+                    &f.word2.word[..],
+                    &f.word2.phonemes[..],
+                    source2,
+                    &f.distance.to_string()[..],
+                    &f.spelling_distance.to_string()[..],
+                ],
+                opts.output_format.delimiter(),
+            )?,
+            OutputFormat::Dot | OutputFormat::GraphMl => {
+                graph.add_edge(&f.word.word, &f.word2.word, Some(f.distance))
+            },
+        }
+    }
+    Ok(results.len())
+}
+
+fn print_minimal_pairs<W: Write>(
+    out: &mut W,
+    graph: &mut SimilarityGraph,
+    opts: &Options,
+    contrasts: &[Contrast],
+) -> io::Result<usize> {
+    let dicts: Vec<&Dictionary> = opts.dicts.iter().collect();
+    let mut groups = group_by_contrast(minimal_pairs(&dicts));
+    if !contrasts.is_empty() {
+        groups.retain(|g| contrasts.contains(&g.0));
+    }
+    for (contrast, pairs) in groups.iter() {
+        let contrast = contrast.to_string();
+        let pairs: Vec<(&Word, &Word, &str)> = pairs
+            .iter()
+            .map(|p| (p.word, p.word2, &opts.sources[p.source][..]))
+            .collect();
+        match opts.output_format {
+            OutputFormat::Text => {
+                write!(out, "{}\t{}\t", contrast, pairs.len())?;
+                print_result(
+                    out,
+                    pairs.iter().map(|p| format!("{}/{}", p.0, p.1)),
+                )?;
+            },
+            OutputFormat::JsonLines => {
+                write_jsonl_contrast(out, &contrast, &pairs)?
+            },
+            OutputFormat::Csv | OutputFormat::Tsv => {
+                let count = pairs.len().to_string();
+                for (word, word2, source) in pairs.iter() {
+                    write_csv_row(
+                        out,
+                        &[
+                            &contrast[..],
+                            &count[..],
+                            &word.word[..],
+                            &word.phonemes[..],
+                            &word2.word[..],
+                            &word2.phonemes[..],
+                            source,
+                        ],
+                        opts.output_format.delimiter(),
+                    )?;
+                }
+            },
+            OutputFormat::Dot | OutputFormat::GraphMl => {
+                for (word, word2, _) in pairs.iter() {
+                    graph.add_edge(&word.word, &word2.word, None);
+                }
+            },
+        }
+    }
+    Ok(groups.len())
+}
+
+fn frequencies(opts: &Options) -> HashMap<String, u64> {
+    let mut res = HashMap::new();
+    for word in opts.dicts.iter().flat_map(|d| d.iter()) {
+        if let Some(frequency) = word.frequency {
+            let f = res.entry(word.word.clone()).or_insert(frequency);
+            *f = frequency.max(*f);
         }
-        words.clear();
     }
     res
 }
@@ -48,21 +365,95 @@ fn search(dict: &Dictionary, dict2: &Dictionary, max_distance: usize) -> usize {
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::from_env_args();
     let opts = Options::from_args(&args)?;
-    let result_count = if opts.max_distance == 0 {
-        let results = if let Some(dict2) = opts.dict2 {
-            WordGroups::from_dicts(opts.dict, dict2)
+    let mut out = open_output_file(&args.output_filename)?;
+    if matches!(opts.output_format, OutputFormat::Csv | OutputFormat::Tsv) {
+        let header = if opts.false_friends.is_some() {
+            FALSE_FRIEND_HEADER
+        } else if opts.minimal_pairs.is_some() {
+            MINIMAL_PAIR_HEADER
         } else {
-            WordGroups::from_dict(opts.dict)
+            PAIR_HEADER
         };
-        print!("{}", results);
-        results.len()
+        write_csv_row(&mut out, header, opts.output_format.delimiter())?;
+    }
+    let mut graph = SimilarityGraph::new();
+    let result_count = if let Some(min_spelling_distance) = opts.false_friends {
+        print_false_friends(&mut out, &mut graph, &opts, min_spelling_distance)?
+    } else if let Some(contrasts) = opts.minimal_pairs.as_ref() {
+        print_minimal_pairs(&mut out, &mut graph, &opts, contrasts)?
+    } else if opts.top > 0 {
+        search(&mut out, &mut graph, &opts, |d, v| {
+            nearest(d, v, opts.metric, opts.max_distance, opts.top)
+        })?
+    } else if opts.max_distance > 0.0 {
+        search(&mut out, &mut graph, &opts, |d, v| {
+            d.find_similar_variants_by(v, opts.metric, opts.max_distance)
+        })?
+    } else if opts.output_format != OutputFormat::Text {
+        let dicts: Vec<&Dictionary> = opts.dicts.iter().collect();
+        let mut groups = group_words(&dicts, &opts.filter);
+        if opts.sort_by_frequency {
+            groups.sort_by_key(|g| {
+                Reverse(g.iter().filter_map(|w| w.0.frequency).max())
+            });
+        }
+        for group in groups.iter() {
+            let words: Vec<(&Word, &str)> = group
+                .iter()
+                .map(|(w, i)| (*w, &opts.sources[*i][..]))
+                .collect();
+            if opts.output_format == OutputFormat::JsonLines {
+                write_jsonl_group(&mut out, &words, opts.metric)?;
+                continue;
+            }
+            for (i, w) in words.iter().enumerate() {
+                for w2 in words.iter().skip(i + 1) {
+                    let d = opts.metric.distance(&w.0.segments, &w2.0.segments);
+                    if opts.output_format.is_graph() {
+                        graph.add_edge(&w.0.word, &w2.0.word, Some(d));
+                    } else {
+                        write_csv_pair(
+                            &mut out,
+                            *w,
+                            *w2,
+                            d,
+                            opts.output_format.delimiter(),
+                        )?;
+                    }
+                }
+            }
+        }
+        groups.len()
     } else {
-        search(
-            &opts.dict,
-            opts.dict2.as_ref().unwrap_or(&opts.dict),
-            opts.max_distance,
-        )
+        let frequencies = if opts.sort_by_frequency {
+            frequencies(&opts)
+        } else {
+            HashMap::new()
+        };
+        let dicts: Vec<&Dictionary> = opts.dicts.iter().collect();
+        let mut results = WordGroups::from_dicts(&dicts, &opts.filter);
+        if opts.sort_by_frequency {
+            results.sort_by_key(|g| {
+                Reverse(g.iter().filter_map(|w| frequencies.get(w)).max())
+            });
+        }
+        write!(out, "{}", results)?;
+        results.len()
     };
+    let result_count = match opts.grouping {
+        Some(grouping) => {
+            let groups = graph.groups(grouping);
+            write!(out, "{}", groups)?;
+            groups.len()
+        },
+        None => result_count,
+    };
+    match opts.output_format {
+        OutputFormat::Dot => graph.write_dot(&mut out)?,
+        OutputFormat::GraphMl => graph.write_graphml(&mut out)?,
+        _ => (),
+    }
+    out.finish()?;
     eprintln!("{} results", result_count);
     Ok(())
 }
@@ -1,67 +1,348 @@
+use std::error::Error;
+use std::fs;
 use std::io::{self, BufRead};
 use std::ops::RangeBounds;
+use std::thread;
+use std::time::UNIX_EPOCH;
 
 use streaming_iterator::StreamingIterator;
 use unicode_segmentation::UnicodeSegmentation;
 
 use crate::args::Args;
 
-use find_similar_words::dictionary::Dictionary;
+use find_similar_words::dictionary::cache::{Cache, CacheError};
+use find_similar_words::dictionary::{
+    Dictionary, IndexType, Word, LANGUAGE_HEADER,
+};
+use find_similar_words::dictionary_format::{
+    Columns, DictionaryFormat, COLUMNS_HEADER,
+};
+use find_similar_words::distance::Metric;
 use find_similar_words::iter::lines;
+use find_similar_words::minimal_pairs::Contrast;
+use find_similar_words::normalization::NormalizationProfile;
+use find_similar_words::output::OutputFormat;
+use find_similar_words::parse_error::{ParseError, ParseLog, ParseMode};
 use find_similar_words::util::open_input_file;
-use find_similar_words::util::to_tsv_pair;
+use find_similar_words::util::to_header_pair;
+use find_similar_words::word_groups::{Grouping, SourceFilter};
+
+const MAX_DELETION_INDEX_DISTANCE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Words,
+    Distances,
+    Alignments,
+}
 
 pub struct Options {
-    pub dict: Dictionary,
-    pub dict2: Option<Dictionary>,
-    pub max_distance: usize,
+    pub dicts: Vec<Dictionary>,
+    pub metric: Metric,
+    pub max_distance: f64,
+    pub top: usize,
+    pub grouping: Option<Grouping>,
+    pub output: Output,
+    pub output_format: OutputFormat,
+    pub sources: Vec<String>,
+    pub filter: SourceFilter,
+    pub false_friends: Option<usize>,
+    pub minimal_pairs: Option<Vec<Contrast>>,
+    pub sort_by_frequency: bool,
+    pub jobs: usize,
 }
 
 impl Options {
     fn load_dict<R: RangeBounds<usize>, I: BufRead>(
+        mut format: DictionaryFormat,
         word_length: &R,
         file: &mut I,
-    ) -> Dictionary {
+        log: &mut ParseLog,
+    ) -> Result<Dictionary, ParseError> {
         let mut res = Dictionary::new();
+        let mut columns = Columns::new();
         let mut lines = lines(file);
         let filter_word_length =
             !(word_length.contains(&0) && word_length.contains(&usize::MAX));
         while let Some(line) = lines.next() {
-            if let Some((word, phonemes)) = to_tsv_pair(line) {
-                if !filter_word_length
-                    || word_length.contains(&word.graphemes(true).count())
-                {
-                    res.add(word, phonemes)
+            if let Some((key, value)) = to_header_pair(line) {
+                if key == LANGUAGE_HEADER {
+                    res.set_language(value);
+                } else if key == COLUMNS_HEADER {
+                    columns = Columns::from_names(value);
                 }
+            } else {
+                if format == DictionaryFormat::Auto {
+                    match DictionaryFormat::detect(line) {
+                        Some(detected) => format = detected,
+                        None if line.trim().is_empty() => continue,
+                        None => {
+                            let reason = format!(
+                                "Could not parse line {:?}",
+                                line.trim()
+                            );
+                            log.report(lines.line_number(), &reason)?;
+                            continue;
+                        },
+                    }
+                }
+                match format.parse_line(line) {
+                    Ok(Some((word, variants))) => {
+                        if filter_word_length
+                            && !word_length
+                                .contains(&word.graphemes(true).count())
+                        {
+                            continue;
+                        }
+                        let (frequency, tags) = if format.has_columns() {
+                            match columns.parse(line) {
+                                Ok(metadata) => metadata,
+                                Err(reason) => {
+                                    log.report(lines.line_number(), &reason)?;
+                                    continue;
+                                },
+                            }
+                        } else {
+                            (None, Vec::new())
+                        };
+                        for phonemes in variants.iter() {
+                            res.add_word(
+                                Word::new(&word, phonemes)
+                                    .with_metadata(frequency, tags.clone()),
+                            );
+                        }
+                    },
+                    Ok(None) => (),
+                    Err(reason) => log.report(lines.line_number(), &reason)?,
+                }
+            }
+        }
+        if let Some(e) = lines.take_error() {
+            log.report(lines.line_number(), &e.to_string())?;
+        }
+        if res.language().is_none() {
+            if let Some(language) = format.language() {
+                res.set_language(language);
             }
         }
-        res
+        Ok(res)
     }
 
-    pub fn from_args(args: &Args) -> io::Result<Self> {
+    fn read_dict(
+        args: &Args,
+        fname: Option<&String>,
+        warnings: &mut Vec<String>,
+    ) -> Result<Dictionary, Box<dyn Error>> {
         let word_length = args.min_word_length..=args.max_word_length;
+        let mode = if args.strict {
+            ParseMode::Strict
+        } else {
+            ParseMode::Lenient
+        };
+        let mut log = ParseLog::new(mode, fname.map(String::as_str));
+        let mut file = open_input_file(&fname)?;
+        let mut res =
+            Self::load_dict(args.format, &word_length, &mut file, &mut log)?;
+        warnings.extend(log.warnings().iter().map(|w| w.to_string()));
+        if args.min_frequency > 0 || args.pos.is_some() {
+            res.retain(|w| {
+                w.frequency.unwrap_or(0) >= args.min_frequency
+                    && args.pos.as_ref().is_none_or(|pos| w.has_tag(pos))
+            });
+        }
+        Ok(res)
+    }
+
+    fn index_params(args: &Args) -> Option<(IndexType, usize)> {
+        if (args.max_distance > 0.0 || args.false_friends) && args.top == 0 {
+            let max_distance =
+                args.metric.max_levenshtein_distance(args.max_distance);
+            let index_type = if args.index == IndexType::Deletions
+                && max_distance > MAX_DELETION_INDEX_DISTANCE
+            {
+                IndexType::BkTree
+            } else {
+                args.index
+            };
+            Some((index_type, max_distance))
+        } else {
+            None
+        }
+    }
+
+    fn read_dicts(
+        args: &Args,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<Dictionary>, Box<dyn Error>> {
+        let mut dicts = Vec::new();
+        if args.input_filenames.is_empty() {
+            dicts.push(Self::read_dict(args, None, warnings)?);
+        }
+        for fname in args.input_filenames.iter() {
+            dicts.push(Self::read_dict(args, Some(fname), warnings)?);
+        }
+
+        if let Some(part) = args.part {
+            for dict in dicts.iter_mut() {
+                dict.map_phonemes(|p| part.extract(p));
+                dict.retain(|w| !w.segments.is_empty());
+            }
+        }
+
+        if let Some(name) = args.normalize_profile.as_ref() {
+            let profile = NormalizationProfile::from_name_or_file(name)?;
+            for dict in dicts.iter_mut() {
+                dict.normalize_with(&profile);
+            }
+        } else if args.normalize {
+            for dict in dicts.iter_mut() {
+                dict.normalize();
+            }
+        }
 
-        let mut file = open_input_file(&args.input_filenames.get(0))?;
-        let mut dict = Self::load_dict(&word_length, &mut file);
-        let mut dict2 = match args.input_filenames.get(1) {
-            Some(fname) => {
-                let mut file = open_input_file(&Some(fname))?;
-                Some(Self::load_dict(&word_length, &mut file))
+        if let Some((index_type, max_distance)) = Self::index_params(args) {
+            let skip = if dicts.len() > 1 { 1 } else { 0 };
+            for dict in dicts.iter_mut().skip(skip) {
+                dict.build_index(index_type, max_distance);
+            }
+        }
+        Ok(dicts)
+    }
+
+    fn cache_key(args: &Args) -> io::Result<Option<String>> {
+        if args.input_filenames.is_empty() {
+            return Ok(None);
+        }
+        let mut files = Vec::new();
+        for fname in args.input_filenames.iter() {
+            let metadata = fs::metadata(fname)?;
+            let modified = metadata
+                .modified()?
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos());
+            files.push((fname, metadata.len(), modified));
+        }
+        Ok(Some(format!(
+            "{:?}",
+            (
+                files,
+                args.format,
+                args.strict,
+                args.normalize,
+                &args.normalize_profile,
+                args.part,
+                (args.min_word_length, args.max_word_length),
+                (args.min_frequency, &args.pos),
+                Self::index_params(args),
+            )
+        )))
+    }
+
+    fn read_cached_dicts(
+        args: &Args,
+        path: &str,
+        warnings: &mut Vec<String>,
+    ) -> Result<Vec<Dictionary>, Box<dyn Error>> {
+        let key = match Self::cache_key(args)? {
+            Some(key) => key,
+            None => {
+                eprintln!("Warning: cache is not used for stdin input");
+                return Self::read_dicts(args, warnings);
             },
-            None => None,
         };
+        match Cache::load(path) {
+            Ok(cache) if cache.key == key && !cache.dicts.is_empty() => {
+                warnings.extend(cache.warnings);
+                return Ok(cache.dicts);
+            },
+            Ok(_) => (),
+            Err(CacheError::Io(e)) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => eprintln!("Warning: {}: {}", path, e),
+        }
+        let dicts = Self::read_dicts(args, warnings)?;
+        let cache = Cache::new(&key, dicts).with_warnings(warnings.clone());
+        cache.save(path)?;
+        Ok(cache.dicts)
+    }
 
-        if args.normalize {
-            dict.normalize();
-            if let Some(d) = dict2.as_mut() {
-                d.normalize();
+    pub fn from_args(args: &Args) -> Result<Self, Box<dyn Error>> {
+        if let Some((index_type, max_distance)) = Self::index_params(args) {
+            if index_type != args.index {
+                eprintln!(
+                    "Warning: deletion index does not support {} edits, using bk-tree",
+                    max_distance
+                );
             }
         }
 
+        let mut warnings = Vec::new();
+        let dicts = match args.cache.as_ref() {
+            Some(path) => Self::read_cached_dicts(args, path, &mut warnings)?,
+            None => Self::read_dicts(args, &mut warnings)?,
+        };
+        for warning in warnings.iter() {
+            eprintln!("Warning: {}", warning);
+        }
+
+        let jobs = match args.jobs {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+
+        let output = if args.alignments {
+            Output::Alignments
+        } else if args.distances || args.top > 0 {
+            Output::Distances
+        } else {
+            Output::Words
+        };
+
+        let grouping = if args.output_format == OutputFormat::Text
+            && !args.false_friends
+            && !args.minimal_pairs
+            && (args.top > 0 || args.max_distance > 0.0)
+        {
+            args.grouping
+        } else {
+            None
+        };
+
+        let sources = if args.input_filenames.is_empty() {
+            vec![String::from("-")]
+        } else {
+            args.input_filenames.clone()
+        };
+
+        let required = args
+            .required_sources
+            .iter()
+            .filter_map(|s| sources.iter().position(|s1| s1 == s))
+            .collect();
+        let min_sources = match args.min_sources {
+            0 => dicts.len().min(2),
+            n => n,
+        };
+        let filter = SourceFilter {
+            min_sources,
+            required,
+        };
+
         Ok(Self {
-            dict,
-            dict2,
+            dicts,
+            metric: args.metric,
             max_distance: args.max_distance,
+            top: args.top,
+            grouping,
+            output,
+            output_format: args.output_format,
+            sources,
+            filter,
+            false_friends: args
+                .false_friends
+                .then_some(args.min_spelling_distance),
+            minimal_pairs: args.minimal_pairs.then(|| args.contrasts.clone()),
+            sort_by_frequency: args.sort_by_frequency,
+            jobs,
         })
     }
 }
@@ -73,6 +354,10 @@ mod tests {
     use std::io::Cursor;
     use std::ops::Range;
 
+    fn lenient() -> ParseLog {
+        ParseLog::new(ParseMode::Lenient, None)
+    }
+
     #[rstest]
     #[case("", 0..10, &[])]
     #[case("xx\tx\nyyy\ty\nzzzz\tz\n", 0..10, &[("xx", "x"), ("yyy", "y"), ("zzzz", "z")])]
@@ -86,7 +371,13 @@ mod tests {
         #[case] expected: &[(&str, &str)],
     ) {
         let mut file = Cursor::new(String::from(file_contents));
-        let dict = Options::load_dict(&word_length, &mut file);
+        let dict = Options::load_dict(
+            DictionaryFormat::Tsv,
+            &word_length,
+            &mut file,
+            &mut lenient(),
+        )
+        .unwrap();
         let mut res: Vec<(&str, &str)> = dict
             .iter()
             .map(|w| (&w.word[..], &w.phonemes[..]))
@@ -94,4 +385,125 @@ mod tests {
         res.sort_by(|x, y| x.0.cmp(y.0));
         assert_eq!(expected, res);
     }
+
+    #[rstest]
+    #[case("", None)]
+    #[case("xx\tx\n", None)]
+    #[case("# language: en\nxx\tx\n", Some("en"))]
+    #[case("xx\tx\n#language:ru\n", Some("ru"))]
+    fn test_options_load_dict_language(
+        #[case] file_contents: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let mut file = Cursor::new(String::from(file_contents));
+        let dict = Options::load_dict(
+            DictionaryFormat::Tsv,
+            &(0..10),
+            &mut file,
+            &mut lenient(),
+        )
+        .unwrap();
+        assert_eq!(expected, dict.language());
+    }
+
+    #[test]
+    fn test_options_load_dict_cmudict() {
+        let mut file = Cursor::new(String::from(
+            ";;; comment\nWORD  W ER1 D\nWORD(2)  W ER0 D\nA  AH0\n",
+        ));
+        let dict = Options::load_dict(
+            DictionaryFormat::CmuDict,
+            &(2..10),
+            &mut file,
+            &mut lenient(),
+        )
+        .unwrap();
+        let res: Vec<(&str, &str)> = dict
+            .iter()
+            .map(|w| (&w.word[..], &w.phonemes[..]))
+            .collect();
+        assert_eq!(vec![("word", "wˈɝd"), ("word", "wɚd")], res);
+        assert_eq!(1, dict.headword_count());
+        assert_eq!(Some("en"), dict.language());
+    }
+
+    #[rstest]
+    #[case(DictionaryFormat::Auto, "a\t/pa/, /pə/\nb\t/ba/\n", &[("a", "pa"), ("a", "pə"), ("b", "ba")])]
+    #[case(DictionaryFormat::IpaDict, "a\t/pa/, /pə/\nb\t/ba/\n", &[("a", "pa"), ("a", "pə"), ("b", "ba")])]
+    #[case(DictionaryFormat::Auto, "a\tp a\na\tp ə\nb\tb a\n", &[("a", "pa"), ("a", "pə"), ("b", "ba")])]
+    #[case(DictionaryFormat::Tsv, "a\tp a\n", &[("a", "p a")])]
+    #[case(DictionaryFormat::Auto, "\n# language: en\nA  AH0\n", &[("a", "ə")])]
+    #[case(DictionaryFormat::Auto, "#tag\tpat\nb\tba\n", &[("#tag", "pat"), ("b", "ba")])]
+    #[case(DictionaryFormat::Auto, "# comment\na\tpa\n", &[("a", "pa")])]
+    fn test_options_load_dict_format(
+        #[case] format: DictionaryFormat,
+        #[case] file_contents: &str,
+        #[case] expected: &[(&str, &str)],
+    ) {
+        let mut file = Cursor::new(String::from(file_contents));
+        let dict =
+            Options::load_dict(format, &(0..10), &mut file, &mut lenient())
+                .unwrap();
+        let res: Vec<(&str, &str)> = dict
+            .iter()
+            .map(|w| (&w.word[..], &w.phonemes[..]))
+            .collect();
+        assert_eq!(expected, res);
+    }
+
+    #[rstest]
+    #[case("a\tx\t3\tNOUN\n", &[("a", Some(3), &["NOUN"][..])])]
+    #[case("a\tx\t\tNOUN,VERB\nb\ty\t5\n", &[("a", None, &["NOUN", "VERB"][..]), ("b", Some(5), &[][..])])]
+    #[case("# columns: word phonemes pos freq\na\tx\tNOUN\t3\n", &[("a", Some(3), &["NOUN"][..])])]
+    #[case("a\t/x/\t3\n", &[("a", Some(3), &[][..])])]
+    #[case("a\tx\tbad\nb\ty\n", &[("b", None, &[][..])])]
+    #[case("A  AH0\n", &[("a", None, &[][..])])]
+    fn test_options_load_dict_metadata(
+        #[case] file_contents: &str,
+        #[case] expected: &[(&str, Option<u64>, &[&str])],
+    ) {
+        let mut file = Cursor::new(String::from(file_contents));
+        let dict = Options::load_dict(
+            DictionaryFormat::Auto,
+            &(0..10),
+            &mut file,
+            &mut lenient(),
+        )
+        .unwrap();
+        let res: Vec<(&str, Option<u64>, Vec<&str>)> = dict
+            .iter()
+            .map(|w| {
+                let tags = w.tags.iter().map(|t| &t[..]).collect();
+                (&w.word[..], w.frequency, tags)
+            })
+            .collect();
+        let expected: Vec<(&str, Option<u64>, Vec<&str>)> = expected
+            .iter()
+            .map(|(w, f, t)| (*w, *f, t.to_vec()))
+            .collect();
+        assert_eq!(expected, res);
+    }
+
+    #[rstest]
+    #[case(ParseMode::Lenient, "a\tx\nb\nc\tz\nd\n", Ok(vec![2, 4]))]
+    #[case(ParseMode::Strict, "a\tx\nb\nc\tz\nd\n", Err(2))]
+    #[case(ParseMode::Strict, "a\tx\n\nc\tz\n", Ok(vec![]))]
+    fn test_options_load_dict_errors(
+        #[case] mode: ParseMode,
+        #[case] file_contents: &str,
+        #[case] expected: Result<Vec<usize>, usize>,
+    ) {
+        let mut file = Cursor::new(String::from(file_contents));
+        let mut log = ParseLog::new(mode, Some("xx"));
+        let res = Options::load_dict(
+            DictionaryFormat::Tsv,
+            &(0..10),
+            &mut file,
+            &mut log,
+        );
+        let res = res
+            .map(|_| log.warnings().iter().map(|w| w.line).collect())
+            .map_err(|e| e.line);
+        assert_eq!(expected, res);
+    }
 }
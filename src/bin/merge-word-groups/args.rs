@@ -1,18 +1,26 @@
 use std::io::Write;
 
-use argparse::{ArgumentParser, Collect};
+use argparse::{ArgumentParser, Collect, Store, StoreOption};
 
+use find_similar_words::output::OutputFormat;
 use find_similar_words::util::ArgParser;
+use find_similar_words::word_groups::Grouping;
 
 #[derive(Debug, PartialEq)]
 pub struct Args {
     pub input_filenames: Vec<String>,
+    pub output_filename: Option<String>,
+    pub output_format: OutputFormat,
+    pub grouping: Grouping,
 }
 
 impl Args {
     pub fn new() -> Self {
         Self {
             input_filenames: Vec::new(),
+            output_filename: None,
+            output_format: OutputFormat::Text,
+            grouping: Grouping::Connected,
         }
     }
 }
@@ -32,9 +40,40 @@ impl ArgParser for Args {
                 Collect,
                 "Files to merge (default: stdin)",
             );
+            parser
+                .refer(&mut opts.output_filename)
+                .metavar("FILE")
+                .add_option(
+                    &["-o", "--output"],
+                    StoreOption,
+                    "Set output file (default: stdout)",
+                );
+            parser
+                .refer(&mut opts.output_format)
+                .metavar("FORMAT")
+                .add_option(
+                    &["-O", "--output-format"],
+                    Store,
+                    "Set output format (text, dot, graphml) (default: text)",
+                );
+            parser
+                .refer(&mut opts.grouping)
+                .metavar("GROUPING")
+                .add_option(
+                    &["-g", "--grouping"],
+                    Store,
+                    "Set grouping (connected, complete, star, diameter:D) (default: connected)",
+                );
             parser.parse(args, stdout, stderr)?;
         }
-        Ok(opts)
+        if opts.output_format == OutputFormat::Text
+            || opts.output_format.is_graph()
+        {
+            Ok(opts)
+        } else {
+            writeln!(stderr, "Unsupported output format").map_err(|_| 2)?;
+            Err(2)
+        }
     }
 }
 
@@ -47,7 +86,12 @@ mod tests {
     #[rstest]
     #[case(&["cmd", "-h"], Err(0))]
     #[case(&["cmd"], Ok(Args::new()))]
-    #[case(&["cmd", "xx", "yy", "zz"], Ok(Args {input_filenames: vec![String::from("xx"), String::from("yy"), String::from("zz")]}))]
+    #[case(&["cmd", "xx", "yy", "zz"], Ok(Args {input_filenames: vec![String::from("xx"), String::from("yy"), String::from("zz")], output_filename: None, output_format: OutputFormat::Text, grouping: Grouping::Connected}))]
+    #[case(&["cmd", "-o", "xx.zst", "yy"], Ok(Args {input_filenames: vec![String::from("yy")], output_filename: Some(String::from("xx.zst")), output_format: OutputFormat::Text, grouping: Grouping::Connected}))]
+    #[case(&["cmd", "-O", "csv"], Err(2))]
+    #[case(&["cmd", "-O", "graphml"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::GraphMl, grouping: Grouping::Connected}))]
+    #[case(&["cmd", "-g", "xx"], Err(2))]
+    #[case(&["cmd", "-g", "star"], Ok(Args {input_filenames: vec![], output_filename: None, output_format: OutputFormat::Text, grouping: Grouping::Star}))]
     fn test_args_parse(
         #[case] args: &[&str],
         #[case] expected: Result<Args, i32>,
@@ -1,14 +1,15 @@
 use std::error::Error;
-use std::fs::File;
-use std::io::{self, BufReader};
+use std::io::{self, Write};
 
 use streaming_iterator::StreamingIterator;
 
 use args::Args;
 
+use find_similar_words::graph::SimilarityGraph;
 use find_similar_words::iter::lines;
-use find_similar_words::util::ArgParser;
-use find_similar_words::word_groups::{GroupBuilder, WordGroups};
+use find_similar_words::output::OutputFormat;
+use find_similar_words::util::{open_input_file, open_output_file, ArgParser};
+use find_similar_words::word_groups::{GroupBuilder, Grouping, WordGroups};
 
 mod args;
 
@@ -16,31 +17,114 @@ fn merge_file<I: io::BufRead>(
     builder: &mut GroupBuilder<usize, String>,
     line_number: &mut usize,
     mut file: I,
-) {
+) -> io::Result<()> {
     let mut lines = lines(&mut file);
     while let Some(line) = lines.next() {
         builder.extend(*line_number, line.split_whitespace());
         *line_number += 1;
     }
+    match lines.take_error() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
-fn merge(fnames: Vec<String>) -> io::Result<WordGroups> {
-    let mut builder = GroupBuilder::<usize, String>::new();
-    let mut line_number = 0usize;
+fn split_distance(word: &str) -> (&str, Option<f64>) {
+    match word.rsplit_once(':') {
+        Some((w, d)) if !w.is_empty() => match d.parse() {
+            Ok(d) => (w, Some(d)),
+            Err(_) => (word, None),
+        },
+        _ => (word, None),
+    }
+}
+
+fn graph_file<I: io::BufRead>(
+    graph: &mut SimilarityGraph,
+    mut file: I,
+) -> io::Result<()> {
+    let mut lines = lines(&mut file);
+    while let Some(line) = lines.next() {
+        let mut words = line.split_whitespace().map(split_distance);
+        if let Some((word, _)) = words.next() {
+            graph.add_node(word);
+            for (word2, distance) in words {
+                graph.add_edge(word, word2, distance);
+            }
+        }
+    }
+    match lines.take_error() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+fn for_each_file<F>(fnames: Vec<String>, mut f: F) -> io::Result<()>
+where
+    F: FnMut(Box<dyn io::BufRead>) -> io::Result<()>,
+{
     if fnames.is_empty() {
-        merge_file(&mut builder, &mut line_number, io::stdin().lock());
+        f(open_input_file(&None::<String>)?)
     } else {
         for fname in fnames {
-            let file = File::open(fname)?;
-            merge_file(&mut builder, &mut line_number, BufReader::new(file));
+            f(open_input_file(&Some(fname))?)?;
         }
+        Ok(())
     }
+}
+
+fn merge(fnames: Vec<String>) -> io::Result<WordGroups> {
+    let mut builder = GroupBuilder::<usize, String>::new();
+    let mut line_number = 0usize;
+    for_each_file(fnames, |file| {
+        merge_file(&mut builder, &mut line_number, file)
+    })?;
     Ok(builder.into_iter().collect())
 }
 
+fn graph(fnames: Vec<String>) -> io::Result<SimilarityGraph> {
+    let mut res = SimilarityGraph::new();
+    for_each_file(fnames, |file| graph_file(&mut res, file))?;
+    Ok(res)
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::from_env_args();
-    let groups = merge(args.input_filenames)?;
-    print!("{}", groups);
+    let mut out = open_output_file(&args.output_filename)?;
+    match args.output_format {
+        OutputFormat::Dot => {
+            graph(args.input_filenames)?.write_dot(&mut out)?
+        },
+        OutputFormat::GraphMl => {
+            graph(args.input_filenames)?.write_graphml(&mut out)?
+        },
+        _ if args.grouping == Grouping::Connected => {
+            write!(out, "{}", merge(args.input_filenames)?)?
+        },
+        _ => {
+            let groups = graph(args.input_filenames)?.groups(args.grouping);
+            write!(out, "{}", groups)?
+        },
+    }
+    out.finish()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("a", ("a", None))]
+    #[case("a:1", ("a", Some(1.0)))]
+    #[case("a:b:0.5", ("a:b", Some(0.5)))]
+    #[case("a:b", ("a:b", None))]
+    #[case(":1", (":1", None))]
+    fn test_split_distance(
+        #[case] word: &str,
+        #[case] expected: (&str, Option<f64>),
+    ) {
+        assert_eq!(expected, split_distance(word));
+    }
+}
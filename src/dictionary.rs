@@ -1,25 +1,65 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
+use std::ptr;
+use std::str::FromStr;
 
-use levenshtein::levenshtein;
+use crate::distance::{levenshtein, Metric};
+use crate::normalization::NormalizationProfile;
+use crate::phoneme::{segments, Segment};
 
-use crate::phoneme::normalize_phonemes;
+use bk_tree::BkTree;
+use deletion_index::DeletionIndex;
+
+pub mod bk_tree;
+pub mod cache;
+pub mod deletion_index;
+
+pub const LANGUAGE_HEADER: &str = "language";
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Word {
     pub word: String,
     pub phonemes: String,
+    pub segments: Vec<Segment>,
+    pub frequency: Option<u64>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexType {
+    None,
+    BkTree,
+    Deletions,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownIndexType {
+    index_type: String,
+}
+
+#[derive(Debug, Clone)]
+enum Index {
+    BkTree(BkTree),
+    Deletions(DeletionIndex<Segment>),
 }
 
 #[derive(Debug, Clone)]
 pub struct Dictionary {
     words: Vec<Word>,
+    headwords: Vec<Vec<usize>>,
+    headword_ids: HashMap<String, usize>,
+    language: Option<String>,
+    index: Option<Index>,
 }
 
-pub struct WordSearchIterator<'a> {
-    word: &'a Word,
+pub struct WordSearchIterator<'a, 'b> {
+    word: &'b Word,
     dict: &'a Dictionary,
-    max_distance: usize,
+    metric: Metric,
+    max_distance: f64,
     index: usize,
+    candidates: Option<std::vec::IntoIter<usize>>,
 }
 
 impl Word {
@@ -27,22 +67,65 @@ impl Word {
         Self {
             word: String::from(word),
             phonemes: String::from(phonemes),
+            segments: segments(phonemes),
+            frequency: None,
+            tags: Vec::new(),
         }
     }
 
+    pub fn with_metadata(
+        mut self,
+        frequency: Option<u64>,
+        tags: Vec<String>,
+    ) -> Self {
+        self.frequency = frequency;
+        self.tags = tags;
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag))
+    }
+
     /*pub fn len(&self) -> usize {
         self.word.graphemes(true).count()
     }*/
 
+    pub fn distance(&self, word: &Word) -> usize {
+        levenshtein(&self.segments, &word.segments)
+    }
+
     pub fn is_similar(&self, word: &Word, max_distance: usize) -> bool {
-        let l1 = self.phonemes.chars().count();
-        let l2 = word.phonemes.chars().count();
-        l1.abs_diff(l2) <= max_distance
-            && levenshtein(&self.phonemes, &word.phonemes) <= max_distance
+        let l1 = self.segments.len();
+        let l2 = word.segments.len();
+        l1.abs_diff(l2) <= max_distance && self.distance(word) <= max_distance
+    }
+
+    pub fn is_similar_by(
+        &self,
+        word: &Word,
+        metric: Metric,
+        max_distance: f64,
+    ) -> bool {
+        match metric {
+            Metric::Levenshtein => self.is_similar(word, max_distance as usize),
+            _ => {
+                metric.distance(&self.segments, &word.segments) <= max_distance
+            },
+        }
     }
 
     pub fn normalize_phonemes(&mut self) {
-        self.phonemes = normalize_phonemes(&self.phonemes);
+        self.normalize_phonemes_with(NormalizationProfile::default_profile());
+    }
+
+    pub fn normalize_phonemes_with(&mut self, profile: &NormalizationProfile) {
+        self.set_phonemes(profile.normalize(&self.phonemes));
+    }
+
+    pub fn set_phonemes(&mut self, phonemes: String) {
+        self.segments = segments(&phonemes);
+        self.phonemes = phonemes;
     }
 }
 
@@ -52,9 +135,38 @@ impl Display for Word {
     }
 }
 
+impl Display for UnknownIndexType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown index type {:?}", self.index_type)
+    }
+}
+
+impl std::error::Error for UnknownIndexType {}
+
+impl FromStr for IndexType {
+    type Err = UnknownIndexType;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "bk-tree" => Ok(Self::BkTree),
+            "deletions" => Ok(Self::Deletions),
+            _ => Err(UnknownIndexType {
+                index_type: String::from(s),
+            }),
+        }
+    }
+}
+
 impl Dictionary {
     pub fn new() -> Self {
-        Self { words: Vec::new() }
+        Self {
+            words: Vec::new(),
+            headwords: Vec::new(),
+            headword_ids: HashMap::new(),
+            language: None,
+            index: None,
+        }
     }
 
     pub fn from_entries(entries: &[(&str, &str)]) -> Self {
@@ -63,14 +175,116 @@ impl Dictionary {
         res
     }
 
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    pub fn set_language(&mut self, language: &str) {
+        self.language = Some(String::from(language));
+    }
+
     pub fn normalize(&mut self) {
+        let profile = match self.language.as_ref() {
+            Some(language) => NormalizationProfile::for_language(language),
+            None => NormalizationProfile::default_profile(),
+        };
+        self.normalize_with(profile);
+    }
+
+    pub fn normalize_with(&mut self, profile: &NormalizationProfile) {
+        self.index = None;
         for word in self.words.iter_mut() {
-            word.normalize_phonemes();
+            word.normalize_phonemes_with(profile);
+        }
+    }
+
+    pub fn map_phonemes<F: FnMut(&str) -> String>(&mut self, mut f: F) {
+        self.index = None;
+        for word in self.words.iter_mut() {
+            word.set_phonemes(f(&word.phonemes));
         }
     }
 
     pub fn add(&mut self, word: &str, phonemes: &str) {
-        self.words.push(Word::new(word, phonemes));
+        self.add_word(Word::new(word, phonemes));
+    }
+
+    pub fn add_word(&mut self, word: Word) {
+        self.index = None;
+        let id = match self.headword_ids.get(&word.word) {
+            Some(id) => *id,
+            None => {
+                self.headword_ids
+                    .insert(word.word.clone(), self.headwords.len());
+                self.headwords.push(Vec::new());
+                self.headwords.len() - 1
+            },
+        };
+        self.headwords[id].push(self.words.len());
+        self.words.push(word);
+    }
+
+    pub fn retain<F: FnMut(&Word) -> bool>(&mut self, f: F) {
+        let words = std::mem::take(&mut self.words);
+        self.headwords.clear();
+        self.headword_ids.clear();
+        for word in words.into_iter().filter(f) {
+            self.add_word(word);
+        }
+    }
+
+    pub fn frequency(&self, word: &str) -> Option<u64> {
+        self.variants(word).iter().filter_map(|w| w.frequency).max()
+    }
+
+    pub fn headword_count(&self) -> usize {
+        self.headwords.len()
+    }
+
+    pub fn headwords(&self) -> impl Iterator<Item = Vec<&Word>> {
+        self.headwords
+            .iter()
+            .map(|ids| ids.iter().map(|i| &self.words[*i]).collect())
+    }
+
+    pub fn variants(&self, word: &str) -> Vec<&Word> {
+        match self.headword_ids.get(word) {
+            Some(id) => self.headwords[*id]
+                .iter()
+                .map(|i| &self.words[*i])
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn index_type(&self) -> IndexType {
+        match self.index {
+            None => IndexType::None,
+            Some(Index::BkTree(_)) => IndexType::BkTree,
+            Some(Index::Deletions(_)) => IndexType::Deletions,
+        }
+    }
+
+    pub fn build_index(&mut self, index_type: IndexType, max_distance: usize) {
+        self.index = match index_type {
+            IndexType::None => None,
+            IndexType::BkTree => {
+                let mut tree = BkTree::new();
+                for i in 0..self.words.len() {
+                    tree.insert(i, |a, b| {
+                        self.words[a].distance(&self.words[b])
+                    });
+                }
+                Some(Index::BkTree(tree))
+            },
+            IndexType::Deletions => {
+                let mut index = DeletionIndex::new(max_distance);
+                for (i, word) in self.words.iter().enumerate() {
+                    index.insert(i, &word.segments);
+                }
+                Some(Index::Deletions(index))
+            },
+        };
     }
 
     /*pub fn extend_streaming<'a, I>(&mut self, mut entries: I)
@@ -85,20 +299,161 @@ impl Dictionary {
         self.words.iter()
     }
 
-    pub fn find_similar<'a>(
+    pub fn find_similar<'a, 'b>(
         &'a self,
-        word: &'a Word,
+        word: &'b Word,
         max_distance: usize,
-    ) -> WordSearchIterator<'a> {
+    ) -> WordSearchIterator<'a, 'b> {
+        self.find_similar_by(word, Metric::Levenshtein, max_distance as f64)
+    }
+
+    pub fn find_similar_by<'a, 'b>(
+        &'a self,
+        word: &'b Word,
+        metric: Metric,
+        max_distance: f64,
+    ) -> WordSearchIterator<'a, 'b> {
+        let max_levenshtein_distance =
+            metric.max_levenshtein_distance(max_distance);
+        let candidates = self.index.as_ref().and_then(|index| {
+            let mut res = match index {
+                Index::BkTree(tree) => tree
+                    .find(max_levenshtein_distance, |i| {
+                        self.words[i].distance(word)
+                    }),
+                Index::Deletions(index) => {
+                    index.find(&word.segments, max_levenshtein_distance)?
+                },
+            };
+            res.retain(|i| {
+                self.words[*i].is_similar_by(word, metric, max_distance)
+            });
+            res.sort_unstable();
+            Some(res.into_iter())
+        });
         WordSearchIterator {
             dict: self,
             word,
+            metric,
             max_distance,
             index: 0,
+            candidates,
+        }
+    }
+
+    pub fn nearest<'a>(
+        &'a self,
+        word: &Word,
+        k: usize,
+    ) -> Vec<(&'a Word, usize)> {
+        self.nearest_by(word, Metric::Levenshtein, k)
+            .into_iter()
+            .map(|(w, d)| (w, d as usize))
+            .collect()
+    }
+
+    pub fn nearest_by<'a>(
+        &'a self,
+        word: &Word,
+        metric: Metric,
+        k: usize,
+    ) -> Vec<(&'a Word, f64)> {
+        self.nearest_variants_by(&[word], metric, k)
+    }
+
+    fn is_variant(&self, variants: &[&Word], word: &Word) -> bool {
+        let words = self.words.as_ptr_range();
+        variants.iter().any(|v| {
+            if words.contains(&ptr::from_ref(*v)) {
+                v.word == word.word
+            } else {
+                v.word == word.word && v.phonemes == word.phonemes
+            }
+        })
+    }
+
+    pub fn find_similar_variants_by<'a>(
+        &'a self,
+        variants: &[&Word],
+        metric: Metric,
+        max_distance: f64,
+    ) -> Vec<(&'a Word, f64)> {
+        let mut res = HashMap::<usize, (&Word, f64)>::new();
+        for variant in variants.iter() {
+            for w in self.find_similar_by(variant, metric, max_distance) {
+                if self.is_variant(variants, w) {
+                    continue;
+                }
+                let d = metric.distance(&variant.segments, &w.segments);
+                let id = self.headword_ids[&w.word];
+                match res.get(&id) {
+                    Some(m) if cmp_nearest(*m, (w, d)).is_le() => (),
+                    _ => {
+                        res.insert(id, (w, d));
+                    },
+                }
+            }
         }
+        let mut res: Vec<(usize, (&Word, f64))> = res.into_iter().collect();
+        res.sort_unstable_by_key(|m| m.0);
+        res.into_iter().map(|m| m.1).collect()
+    }
+
+    pub fn nearest_variants_by<'a>(
+        &'a self,
+        variants: &[&Word],
+        metric: Metric,
+        k: usize,
+    ) -> Vec<(&'a Word, f64)> {
+        let mut res: Vec<(&Word, f64)> = Vec::with_capacity(k + 1);
+        if k == 0 {
+            return res;
+        }
+        for ids in self.headwords.iter() {
+            let mut best: Option<(&Word, f64)> = None;
+            for w in ids.iter().map(|i| &self.words[*i]) {
+                if self.is_variant(variants, w) {
+                    continue;
+                }
+                for variant in variants.iter() {
+                    let max = match (best, res.len() == k) {
+                        (Some(b), true) => b.1.min(res[k - 1].1),
+                        (Some(b), false) => b.1,
+                        (None, true) => res[k - 1].1,
+                        (None, false) => f64::INFINITY,
+                    };
+                    if metric == Metric::Levenshtein {
+                        let min =
+                            w.segments.len().abs_diff(variant.segments.len());
+                        if min as f64 > max {
+                            continue;
+                        }
+                    }
+                    let d = metric.distance(&variant.segments, &w.segments);
+                    if best.is_none_or(|b| cmp_nearest((w, d), b).is_lt()) {
+                        best = Some((w, d));
+                    }
+                }
+            }
+            if let Some(best) = best {
+                let pos =
+                    res.partition_point(|r| cmp_nearest(*r, best).is_le());
+                if pos < k {
+                    res.insert(pos, best);
+                    res.truncate(k);
+                }
+            }
+        }
+        res
     }
 }
 
+fn cmp_nearest(a: (&Word, f64), b: (&Word, f64)) -> Ordering {
+    a.1.total_cmp(&b.1)
+        .then_with(|| a.0.word.cmp(&b.0.word))
+        .then_with(|| a.0.phonemes.cmp(&b.0.phonemes))
+}
+
 impl Default for Dictionary {
     fn default() -> Self {
         Self::new()
@@ -125,17 +480,19 @@ impl<'a> Extend<&'a (&'a str, &'a str)> for Dictionary {
     }
 }
 
-impl<'a> Iterator for WordSearchIterator<'a> {
+impl<'a, 'b> Iterator for WordSearchIterator<'a, 'b> {
     type Item = &'a Word;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(candidates) = self.candidates.as_mut() {
+            return candidates.next().map(|i| &self.dict.words[i]);
+        }
         if self.index >= self.dict.words.len() {
             return None;
         }
-        let res = self.dict.words[self.index..]
-            .iter()
-            .enumerate()
-            .find(|w| w.1.is_similar(self.word, self.max_distance));
+        let res = self.dict.words[self.index..].iter().enumerate().find(|w| {
+            w.1.is_similar_by(self.word, self.metric, self.max_distance)
+        });
         match res {
             Some((i, w)) => {
                 self.index += i + 1;
@@ -166,6 +523,30 @@ mod tests {
         let w = Word::new(word, phonemes);
         assert_eq!(word, w.word);
         assert_eq!(phonemes, w.phonemes);
+        assert_eq!(segments(phonemes), w.segments);
+    }
+
+    #[rstest]
+    #[case("ˈtʃɜːtʃ", "tʃetʃ", 1)]
+    #[case("m ʌ nʲ ɪ", "manʲi", 2)]
+    #[case("ˈaɪ", "ˌaɪ", 0)]
+    fn test_word_distance(
+        #[case] phonemes: &str,
+        #[case] phonemes2: &str,
+        #[case] expected: usize,
+    ) {
+        let w = Word::new("w", phonemes);
+        let w2 = Word::new("w2", phonemes2);
+        assert_eq!(expected, w.distance(&w2));
+        assert_eq!(expected, w2.distance(&w));
+    }
+
+    #[test]
+    fn test_word_normalize_phonemes() {
+        let mut w = Word::new("w", "ʌbɹiˌviejˈʃʌnz");
+        w.normalize_phonemes();
+        assert_eq!("abriviejʃanz", w.phonemes);
+        assert_eq!(segments("abriviejʃanz"), w.segments);
     }
 
     #[rstest]
@@ -189,6 +570,24 @@ mod tests {
     fn test_dict_init() {
         let dict = Dictionary::new();
         assert_eq!(0, dict.iter().count());
+        assert_eq!(None, dict.language());
+    }
+
+    #[rstest]
+    #[case(None, "bit")]
+    #[case(Some("ru"), "bit")]
+    #[case(Some("en"), "bɪt")]
+    fn test_dict_normalize_language(
+        #[case] language: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let mut dict = Dictionary::from_entries(&[("w", "bˈɪt")]);
+        if let Some(language) = language {
+            dict.set_language(language);
+        }
+        assert_eq!(language, dict.language());
+        dict.normalize();
+        assert_eq!(expected, dict.iter().next().unwrap().phonemes);
     }
 
     #[rstest]
@@ -201,6 +600,138 @@ mod tests {
         assert_eq!(items, entries(&dict));
     }
 
+    #[test]
+    fn test_dict_retain() {
+        let mut dict = Dictionary::new();
+        dict.add_word(Word::new("a", "a").with_metadata(Some(3), vec![]));
+        dict.add_word(Word::new("b", "b").with_metadata(Some(1), vec![]));
+        dict.add_word(Word::new("a", "aa").with_metadata(Some(5), vec![]));
+        dict.add_word(Word::new("c", "c"));
+        assert_eq!(Some(5), dict.frequency("a"));
+        assert_eq!(None, dict.frequency("c"));
+        dict.retain(|w| w.frequency.is_some_and(|f| f >= 3));
+        assert_eq!(1, dict.headword_count());
+        assert_eq!(vec![("a", "a"), ("a", "aa")], entries(&dict));
+        assert_eq!(Vec::<&Word>::new(), dict.variants("b"));
+    }
+
+    #[test]
+    fn test_dict_map_phonemes() {
+        let mut dict = Dictionary::from_entries(&[("a", "ab"), ("b", "cd")]);
+        dict.map_phonemes(|p| p.replace('b', "x"));
+        assert_eq!(vec![("a", "ax"), ("b", "cd")], entries(&dict));
+        assert_eq!(segments("ax"), dict.iter().next().unwrap().segments);
+    }
+
+    #[rstest]
+    #[case(&[], "NOUN", false)]
+    #[case(&["NOUN"], "NOUN", true)]
+    #[case(&["VERB", "noun"], "NOUN", true)]
+    #[case(&["VERB"], "NOUN", false)]
+    fn test_word_has_tag(
+        #[case] tags: &[&str],
+        #[case] tag: &str,
+        #[case] expected: bool,
+    ) {
+        let tags = tags.iter().map(|t| String::from(*t)).collect();
+        let w = Word::new("w", "w").with_metadata(None, tags);
+        assert_eq!(expected, w.has_tag(tag));
+    }
+
+    #[test]
+    fn test_dict_variants() {
+        let dict = Dictionary::from_entries(&[
+            ("a", "pat"),
+            ("b", "bat"),
+            ("a", "pæt"),
+        ]);
+        assert_eq!(3, dict.iter().count());
+        assert_eq!(2, dict.headword_count());
+        let headwords: Vec<Vec<&str>> = dict
+            .headwords()
+            .map(|v| v.iter().map(|w| &w.phonemes[..]).collect())
+            .collect();
+        assert_eq!(vec![vec!["pat", "pæt"], vec!["bat"]], headwords);
+        let variants: Vec<&str> =
+            dict.variants("a").iter().map(|w| &w.phonemes[..]).collect();
+        assert_eq!(vec!["pat", "pæt"], variants);
+        assert!(dict.variants("c").is_empty());
+    }
+
+    #[rstest]
+    #[case(&["pat"], 0.0, &[("c", 0.0)])]
+    #[case(&["pat"], 1.0, &[("b", 1.0), ("c", 0.0), ("d", 1.0)])]
+    #[case(&["pat", "pet"], 1.0, &[("b", 1.0), ("c", 0.0), ("d", 0.0)])]
+    #[case(&["pet"], 1.0, &[("b", 1.0), ("c", 1.0), ("d", 0.0)])]
+    fn test_dict_find_similar_variants_by(
+        #[case] variants: &[&str],
+        #[case] max_distance: f64,
+        #[case] expected: &[(&str, f64)],
+    ) {
+        let dict = Dictionary::from_entries(&[
+            ("a", "pat"),
+            ("b", "bat"),
+            ("a", "pet"),
+            ("b", "bet"),
+            ("c", "pat"),
+            ("d", "pet"),
+        ]);
+        let variants: Vec<&Word> = dict
+            .variants("a")
+            .into_iter()
+            .filter(|w| variants.contains(&&w.phonemes[..]))
+            .collect();
+        let res: Vec<(&str, f64)> = dict
+            .find_similar_variants_by(
+                &variants,
+                Metric::Levenshtein,
+                max_distance,
+            )
+            .into_iter()
+            .map(|(w, d)| (&w.word[..], d))
+            .collect();
+        assert_eq!(expected, res);
+        let res: Vec<&str> = dict
+            .nearest_variants_by(&variants, Metric::Levenshtein, 10)
+            .into_iter()
+            .filter(|m| m.1 <= max_distance)
+            .map(|(w, _)| &w.word[..])
+            .collect();
+        let mut expected: Vec<(&str, f64)> = expected.to_vec();
+        expected.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(b.0)));
+        assert_eq!(expected.iter().map(|m| m.0).collect::<Vec<&str>>(), res);
+    }
+
+    #[test]
+    fn test_dict_find_similar_variants_other_dict() {
+        let mut dict = Dictionary::new();
+        dict.add_word(
+            Word::new("taxi", "taksi")
+                .with_metadata(Some(5), vec![String::from("NOUN")]),
+        );
+        let dict2 = Dictionary::from_entries(&[
+            ("taxi", "taksi"),
+            ("taxi", "taksɪ"),
+            ("tax", "taks"),
+        ]);
+        let variants = dict.variants("taxi");
+        let res: Vec<(&str, &str)> = dict2
+            .find_similar_variants_by(&variants, Metric::Levenshtein, 1.0)
+            .into_iter()
+            .map(|(w, _)| (&w.word[..], &w.phonemes[..]))
+            .collect();
+        assert_eq!(vec![("taxi", "taksɪ"), ("tax", "taks")], res);
+        let res: Vec<(&str, &str)> = dict2
+            .nearest_variants_by(&variants, Metric::Levenshtein, 2)
+            .into_iter()
+            .map(|(w, _)| (&w.word[..], &w.phonemes[..]))
+            .collect();
+        assert_eq!(vec![("tax", "taks"), ("taxi", "taksɪ")], res);
+        assert!(dict
+            .find_similar_variants_by(&variants, Metric::Levenshtein, 1.0)
+            .is_empty());
+    }
+
     #[rstest]
     #[case(&[("w", "p"), ("w2", "p2")])]
     fn test_dict_extend(#[case] items: &[(&str, &str)]) {
@@ -245,8 +776,12 @@ mod tests {
         #[case] search: (&str, &str),
         #[case] max_distance: usize,
         #[case] expected: &[&str],
+        #[values(IndexType::None, IndexType::BkTree, IndexType::Deletions)]
+        index_type: IndexType,
     ) {
-        let dict = Dictionary::from_entries(items);
+        let mut dict = Dictionary::from_entries(items);
+        dict.build_index(index_type, max_distance);
+        assert_eq!(index_type, dict.index_type());
         let search = Word::new(search.0, search.1);
         let results = dict
             .find_similar(&search, max_distance)
@@ -254,4 +789,169 @@ mod tests {
             .collect::<Vec<&str>>();
         assert_eq!(expected, results);
     }
+
+    #[rstest]
+    #[case(("w", "pat"), ("w2", "bad"), Metric::Levenshtein, 1.5, false)]
+    #[case(("w", "pat"), ("w2", "bad"), Metric::Levenshtein, 2.0, true)]
+    #[case(("w", "pat"), ("w2", "bad"), Metric::Features, 0.25, false)]
+    #[case(("w", "pat"), ("w2", "bad"), Metric::Features, 0.5, true)]
+    fn test_word_is_similar_by(
+        #[case] word: (&str, &str),
+        #[case] word2: (&str, &str),
+        #[case] metric: Metric,
+        #[case] max_distance: f64,
+        #[case] expected: bool,
+    ) {
+        let w = Word::new(word.0, word.1);
+        let w2 = Word::new(word2.0, word2.1);
+        assert_eq!(expected, w.is_similar_by(&w2, metric, max_distance));
+    }
+
+    #[rstest]
+    #[case(("x", "pat"), Metric::Levenshtein, 1.0, &["a", "b", "d", "e"])]
+    #[case(("x", "pat"), Metric::Features, 0.25, &["a", "b"])]
+    #[case(("x", "pat"), Metric::Features, 0.5, &["a", "b", "c", "d"])]
+    #[case(("x", "pat"), Metric::Features, 1.0, &["a", "b", "c", "d", "e"])]
+    fn test_dict_find_similar_by(
+        #[case] search: (&str, &str),
+        #[case] metric: Metric,
+        #[case] max_distance: f64,
+        #[case] expected: &[&str],
+        #[values(IndexType::None, IndexType::BkTree, IndexType::Deletions)]
+        index_type: IndexType,
+    ) {
+        let items = [
+            ("a", "pat"),
+            ("b", "bat"),
+            ("c", "bad"),
+            ("d", "pəat"),
+            ("e", "pt"),
+            ("f", "ʃiŋ"),
+        ];
+        let mut dict = Dictionary::from_entries(&items);
+        dict.build_index(
+            index_type,
+            metric.max_levenshtein_distance(max_distance),
+        );
+        let search = Word::new(search.0, search.1);
+        let results = dict
+            .find_similar_by(&search, metric, max_distance)
+            .map(|w| &w.word[..])
+            .collect::<Vec<&str>>();
+        assert_eq!(expected, results);
+    }
+
+    #[rstest]
+    #[case(("x", "pat"), 0, &[])]
+    #[case(("x", "pat"), 1, &[("a", 0)])]
+    #[case(("x", "pat"), 3, &[("a", 0), ("b", 1), ("d", 1)])]
+    #[case(("a", "pat"), 3, &[("b", 1), ("d", 1), ("e", 1)])]
+    #[case(("x", "ʃiŋ"), 2, &[("f", 0), ("a", 3)])]
+    #[case(("x", "pat"), 10, &[("a", 0), ("b", 1), ("d", 1), ("e", 1), ("c", 2), ("f", 3)])]
+    fn test_dict_nearest(
+        #[case] search: (&str, &str),
+        #[case] k: usize,
+        #[case] expected: &[(&str, usize)],
+    ) {
+        let items = [
+            ("f", "ʃiŋ"),
+            ("e", "pt"),
+            ("d", "pəat"),
+            ("c", "bad"),
+            ("b", "bat"),
+            ("a", "pat"),
+        ];
+        let dict = Dictionary::from_entries(&items);
+        let search = Word::new(search.0, search.1);
+        let results: Vec<(&str, usize)> = dict
+            .nearest(&search, k)
+            .into_iter()
+            .map(|(w, d)| (&w.word[..], d))
+            .collect();
+        assert_eq!(expected, results);
+    }
+
+    #[rstest]
+    #[case(("x", "pat"), 2, &[("a", 0.0), ("b", 0.25)])]
+    #[case(("x", "pat"), 4, &[("a", 0.0), ("b", 0.25), ("c", 0.5), ("d", 0.5)])]
+    fn test_dict_nearest_by(
+        #[case] search: (&str, &str),
+        #[case] k: usize,
+        #[case] expected: &[(&str, f64)],
+    ) {
+        let items = [
+            ("f", "ʃiŋ"),
+            ("e", "pt"),
+            ("d", "pəat"),
+            ("c", "bad"),
+            ("b", "bat"),
+            ("a", "pat"),
+        ];
+        let dict = Dictionary::from_entries(&items);
+        let search = Word::new(search.0, search.1);
+        let results: Vec<(&str, f64)> = dict
+            .nearest_by(&search, Metric::Features, k)
+            .into_iter()
+            .map(|(w, d)| (&w.word[..], d))
+            .collect();
+        assert_eq!(expected, results);
+    }
+
+    #[rstest]
+    #[case("none", Ok(IndexType::None))]
+    #[case("bk-tree", Ok(IndexType::BkTree))]
+    #[case("deletions", Ok(IndexType::Deletions))]
+    #[case("xx", Err(UnknownIndexType { index_type: String::from("xx") }))]
+    fn test_index_type_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<IndexType, UnknownIndexType>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(1)]
+    #[case(2)]
+    fn test_dict_find_similar_index(
+        #[case] max_distance: usize,
+        #[values(IndexType::BkTree, IndexType::Deletions)]
+        index_type: IndexType,
+    ) {
+        let items = [
+            ("a", "kat"),
+            ("b", "bat"),
+            ("c", "bad"),
+            ("d", "bid"),
+            ("e", "kat"),
+            ("f", "skat"),
+            ("g", "at"),
+            ("h", ""),
+        ];
+        let dict = Dictionary::from_entries(&items);
+        let mut indexed = dict.clone();
+        indexed.build_index(index_type, max_distance);
+        for search_distance in 0..=max_distance + 1 {
+            let expected: Vec<Vec<&str>> = dict
+                .iter()
+                .map(|w| {
+                    dict.find_similar(w, search_distance)
+                        .map(|w| &w.word[..])
+                        .collect()
+                })
+                .collect();
+            let res: Vec<Vec<&str>> = indexed
+                .iter()
+                .map(|w| {
+                    indexed
+                        .find_similar(w, search_distance)
+                        .map(|w| &w.word[..])
+                        .collect()
+                })
+                .collect();
+            assert_eq!(expected, res);
+        }
+        indexed.add("i", "kat");
+        assert_eq!(IndexType::None, indexed.index_type());
+    }
 }
@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+
+use super::cache::{CacheError, Decode, Encode};
+
+#[derive(Debug, Clone)]
+struct Node {
+    item: usize,
+    children: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BkTree {
+    nodes: Vec<Node>,
+}
+
+impl BkTree {
+    pub fn new() -> Self {
+        Self { nodes: Vec::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes.iter().map(|n| n.item)
+    }
+
+    pub fn insert<F>(&mut self, item: usize, distance: F)
+    where
+        F: Fn(usize, usize) -> usize,
+    {
+        let new_node = self.nodes.len();
+        if new_node > 0 {
+            let mut node = 0_usize;
+            loop {
+                let d = distance(self.nodes[node].item, item);
+                let child = self.nodes[node]
+                    .children
+                    .iter()
+                    .find(|c| c.0 == d)
+                    .map(|c| c.1);
+                match child {
+                    Some(child) => {
+                        node = child;
+                    },
+                    None => {
+                        self.nodes[node].children.push((d, new_node));
+                        break;
+                    },
+                }
+            }
+        }
+        self.nodes.push(Node {
+            item,
+            children: Vec::new(),
+        });
+    }
+
+    pub fn find<F>(&self, max_distance: usize, distance: F) -> Vec<usize>
+    where
+        F: Fn(usize) -> usize,
+    {
+        let mut res = Vec::new();
+        if self.nodes.is_empty() {
+            return res;
+        }
+        let mut stack = Vec::from([0_usize]);
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            let d = distance(node.item);
+            if d <= max_distance {
+                res.push(node.item);
+            }
+            let min = d.saturating_sub(max_distance);
+            let max = d.saturating_add(max_distance);
+            stack.extend(
+                node.children
+                    .iter()
+                    .filter(|c| min <= c.0 && c.0 <= max)
+                    .map(|c| c.1),
+            );
+        }
+        res
+    }
+}
+
+impl Encode for BkTree {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.nodes.len().encode(w)?;
+        for node in self.nodes.iter() {
+            node.item.encode(w)?;
+            node.children.encode(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl Decode for BkTree {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let len = usize::decode(buf)?;
+        let mut nodes = Vec::with_capacity(len.min(buf.len()));
+        for i in 0..len {
+            let item = usize::decode(buf)?;
+            let children: Vec<(usize, usize)> = Vec::decode(buf)?;
+            if children.iter().any(|c| c.1 <= i || c.1 >= len) {
+                return Err(CacheError::InvalidFormat);
+            }
+            nodes.push(Node { item, children });
+        }
+        Ok(Self { nodes })
+    }
+}
+
+impl Default for BkTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distance::levenshtein;
+    use rstest::*;
+
+    #[test]
+    fn test_bk_tree_init() {
+        let tree = BkTree::new();
+        assert_eq!(0, tree.len());
+        assert!(tree.is_empty());
+        assert_eq!(Vec::<usize>::new(), tree.find(10, |_| 0));
+    }
+
+    #[rstest]
+    #[case(&["a", "b", "ab", "abc", "xyz", "bc"], "b", 0, &["b"])]
+    #[case(&["a", "b", "ab", "abc", "xyz", "bc"], "b", 1, &["a", "ab", "b", "bc"])]
+    #[case(&["a", "b", "ab", "abc", "xyz", "bc"], "abd", 1, &["ab", "abc"])]
+    #[case(&["a", "b", "ab", "abc", "xyz", "bc"], "abd", 3, &["a", "ab", "abc", "b", "bc", "xyz"])]
+    #[case(&["a", "a", "a"], "a", 0, &["a", "a", "a"])]
+    fn test_bk_tree_find(
+        #[case] items: &[&str],
+        #[case] search: &str,
+        #[case] max_distance: usize,
+        #[case] expected: &[&str],
+    ) {
+        let items_chars: Vec<Vec<char>> =
+            items.iter().map(|i| i.chars().collect()).collect();
+        let search_chars: Vec<char> = search.chars().collect();
+        let mut tree = BkTree::new();
+        for i in 0..items.len() {
+            tree.insert(i, |a, b| {
+                levenshtein(&items_chars[a], &items_chars[b])
+            });
+        }
+        assert_eq!(items.len(), tree.len());
+        let mut res: Vec<&str> = tree
+            .find(max_distance, |i| {
+                levenshtein(&items_chars[i], &search_chars)
+            })
+            .into_iter()
+            .map(|i| items[i])
+            .collect();
+        res.sort();
+        assert_eq!(expected, res);
+    }
+}
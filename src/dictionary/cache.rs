@@ -0,0 +1,438 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::{Dictionary, Index, Word};
+use crate::phoneme::Segment;
+
+pub const CACHE_MAGIC: &[u8; 4] = b"FSWC";
+pub const CACHE_VERSION: u32 = 2;
+
+#[derive(Debug)]
+pub enum CacheError {
+    Io(io::Error),
+    InvalidFormat,
+    UnsupportedVersion(u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    pub key: String,
+    pub dicts: Vec<Dictionary>,
+    pub warnings: Vec<String>,
+}
+
+pub trait Encode {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()>;
+}
+
+pub trait Decode
+where
+    Self: Sized,
+{
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError>;
+}
+
+impl Display for CacheError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::InvalidFormat => write!(f, "Invalid cache file"),
+            Self::UnsupportedVersion(v) => {
+                write!(f, "Unsupported cache file version {}", v)
+            },
+        }
+    }
+}
+
+impl Error for CacheError {}
+
+impl From<io::Error> for CacheError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], CacheError> {
+    if buf.len() < len {
+        return Err(CacheError::InvalidFormat);
+    }
+    let (res, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(res)
+}
+
+impl Encode for u8 {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&[*self])
+    }
+}
+
+impl Decode for u8 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        Ok(take(buf, 1)?[0])
+    }
+}
+
+impl Encode for u32 {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.to_le_bytes())
+    }
+}
+
+impl Decode for u32 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let bytes = take(buf, 4)?;
+        Ok(Self::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl Encode for u64 {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(&self.to_le_bytes())
+    }
+}
+
+impl Decode for u64 {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let bytes = take(buf, 8)?;
+        Ok(Self::from_le_bytes(bytes.try_into().unwrap()))
+    }
+}
+
+impl Encode for usize {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (*self as u64).encode(w)
+    }
+}
+
+impl Decode for usize {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        Self::try_from(u64::decode(buf)?).map_err(|_| CacheError::InvalidFormat)
+    }
+}
+
+impl Encode for str {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.len().encode(w)?;
+        w.write_all(self.as_bytes())
+    }
+}
+
+impl Encode for String {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self[..].encode(w)
+    }
+}
+
+impl Decode for String {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let len = usize::decode(buf)?;
+        let bytes = take(buf, len)?;
+        match std::str::from_utf8(bytes) {
+            Ok(s) => Ok(Self::from(s)),
+            Err(_) => Err(CacheError::InvalidFormat),
+        }
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            Some(value) => {
+                1u8.encode(w)?;
+                value.encode(w)
+            },
+            None => 0u8.encode(w),
+        }
+    }
+}
+
+impl<T: Decode> Decode for Option<T> {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        match u8::decode(buf)? {
+            0 => Ok(None),
+            1 => Ok(Some(T::decode(buf)?)),
+            _ => Err(CacheError::InvalidFormat),
+        }
+    }
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.len().encode(w)?;
+        for item in self.iter() {
+            item.encode(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self[..].encode(w)
+    }
+}
+
+impl<T: Decode> Decode for Vec<T> {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let len = usize::decode(buf)?;
+        let mut res = Vec::with_capacity(len.min(buf.len()));
+        for _ in 0..len {
+            res.push(T::decode(buf)?);
+        }
+        Ok(res)
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.0.encode(w)?;
+        self.1.encode(w)
+    }
+}
+
+impl<A: Decode, B: Decode> Decode for (A, B) {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        Ok((A::decode(buf)?, B::decode(buf)?))
+    }
+}
+
+impl Encode for Segment {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.base().encode(w)?;
+        self.modifiers().encode(w)
+    }
+}
+
+impl Decode for Segment {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let base = String::decode(buf)?;
+        let modifiers = String::decode(buf)?;
+        Ok(Self::new(&base, &modifiers))
+    }
+}
+
+impl Encode for Word {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.word.encode(w)?;
+        self.phonemes.encode(w)?;
+        self.frequency.encode(w)?;
+        self.tags.encode(w)
+    }
+}
+
+impl Decode for Word {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let word = String::decode(buf)?;
+        let phonemes = String::decode(buf)?;
+        let frequency = Option::decode(buf)?;
+        let tags = Vec::decode(buf)?;
+        Ok(Self::new(&word, &phonemes).with_metadata(frequency, tags))
+    }
+}
+
+impl Encode for Index {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        match self {
+            Self::BkTree(tree) => {
+                0u8.encode(w)?;
+                tree.encode(w)
+            },
+            Self::Deletions(index) => {
+                1u8.encode(w)?;
+                index.encode(w)
+            },
+        }
+    }
+}
+
+impl Decode for Index {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        match u8::decode(buf)? {
+            0 => Ok(Self::BkTree(Decode::decode(buf)?)),
+            1 => Ok(Self::Deletions(Decode::decode(buf)?)),
+            _ => Err(CacheError::InvalidFormat),
+        }
+    }
+}
+
+impl Encode for Dictionary {
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.language.encode(w)?;
+        self.words.encode(w)?;
+        self.index.encode(w)
+    }
+}
+
+impl Decode for Dictionary {
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let mut res = Self::new();
+        res.language = Option::decode(buf)?;
+        for word in Vec::<Word>::decode(buf)? {
+            res.add_word(word);
+        }
+        res.index = Option::decode(buf)?;
+        let len = res.words.len();
+        let valid = match &res.index {
+            None => true,
+            Some(Index::BkTree(tree)) => tree.items().all(|i| i < len),
+            Some(Index::Deletions(index)) => index.items().all(|i| i < len),
+        };
+        if !valid {
+            return Err(CacheError::InvalidFormat);
+        }
+        Ok(res)
+    }
+}
+
+impl Cache {
+    pub fn new(key: &str, dicts: Vec<Dictionary>) -> Self {
+        Self {
+            key: String::from(key),
+            dicts,
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        w.write_all(CACHE_MAGIC)?;
+        CACHE_VERSION.encode(w)?;
+        self.key.encode(w)?;
+        self.warnings.encode(w)?;
+        self.dicts.encode(w)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        self.write(&mut file)?;
+        file.flush()
+    }
+
+    pub fn from_bytes(mut buf: &[u8]) -> Result<Self, CacheError> {
+        if take(&mut buf, CACHE_MAGIC.len())? != CACHE_MAGIC {
+            return Err(CacheError::InvalidFormat);
+        }
+        let version = u32::decode(&mut buf)?;
+        if version != CACHE_VERSION {
+            return Err(CacheError::UnsupportedVersion(version));
+        }
+        let key = String::decode(&mut buf)?;
+        let warnings = Vec::decode(&mut buf)?;
+        let dicts = Vec::decode(&mut buf)?;
+        if !buf.is_empty() {
+            return Err(CacheError::InvalidFormat);
+        }
+        Ok(Self {
+            key,
+            dicts,
+            warnings,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CacheError> {
+        let file = File::open(path)?;
+        // The map is only read while decoding, everything is copied out.
+        let map = unsafe { Mmap::map(&file)? };
+        Self::from_bytes(&map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dictionary::bk_tree::BkTree;
+    use crate::dictionary::deletion_index::DeletionIndex;
+    use crate::dictionary::IndexType;
+    use rstest::*;
+
+    fn entries(dict: &Dictionary) -> Vec<(&str, &str, Option<u64>)> {
+        dict.iter()
+            .map(|w| (&w.word[..], &w.phonemes[..], w.frequency))
+            .collect()
+    }
+
+    #[rstest]
+    #[case(IndexType::None)]
+    #[case(IndexType::BkTree)]
+    #[case(IndexType::Deletions)]
+    fn test_cache_roundtrip(#[case] index_type: IndexType) {
+        let mut dict = Dictionary::new();
+        dict.set_language("en");
+        dict.add_word(Word::new("a", "ab").with_metadata(Some(3), vec![]));
+        dict.add("b", "b");
+        dict.add_word(
+            Word::new("a", "aː").with_metadata(None, vec![String::from("X")]),
+        );
+        dict.build_index(index_type, 1);
+        let mut buf = Vec::new();
+        Cache::new("key", vec![dict.clone()])
+            .with_warnings(vec![String::from("warning")])
+            .write(&mut buf)
+            .unwrap();
+
+        let cache = Cache::from_bytes(&buf).unwrap();
+        assert_eq!("key", cache.key);
+        assert_eq!(vec![String::from("warning")], cache.warnings);
+        assert_eq!(1, cache.dicts.len());
+        let res = &cache.dicts[0];
+        assert_eq!(entries(&dict), entries(res));
+        assert_eq!(Some("en"), res.language());
+        assert_eq!(index_type, res.index_type());
+        assert_eq!(2, res.variants("a").len());
+        assert_eq!(vec![String::from("X")], res.variants("a")[1].tags);
+        let search = Word::new("c", "b");
+        let mut similar: Vec<&str> = res
+            .find_similar(&search, 1)
+            .map(|w| &w.phonemes[..])
+            .collect();
+        similar.sort();
+        assert_eq!(vec!["ab", "aː", "b"], similar);
+    }
+
+    #[rstest]
+    #[case(b"", "Invalid cache file")]
+    #[case(b"XXXX\x01\0\0\0", "Invalid cache file")]
+    #[case(b"FSWC\x01\0\0\0", "Unsupported cache file version 1")]
+    #[case(b"FSWC\x02\0\0\0\x03\0\0\0\0\0\0\0ab", "Invalid cache file")]
+    fn test_cache_invalid(#[case] input: &[u8], #[case] expected: &str) {
+        let res = Cache::from_bytes(input).map(|_| ());
+        assert_eq!(Err(String::from(expected)), res.map_err(|e| e.to_string()));
+    }
+
+    #[rstest]
+    #[case(IndexType::BkTree)]
+    #[case(IndexType::Deletions)]
+    fn test_cache_invalid_index(#[case] index_type: IndexType) {
+        let mut dict = Dictionary::new();
+        dict.add("a", "ab");
+        dict.index = match index_type {
+            IndexType::BkTree => {
+                let mut tree = BkTree::new();
+                tree.insert(1, |_, _| 0);
+                Some(Index::BkTree(tree))
+            },
+            _ => {
+                let mut index = DeletionIndex::new(1);
+                index.insert(1, &dict.words[0].segments);
+                Some(Index::Deletions(index))
+            },
+        };
+        let mut buf = Vec::new();
+        Cache::new("key", vec![dict]).write(&mut buf).unwrap();
+        let res = Cache::from_bytes(&buf).map(|_| ());
+        assert_eq!(
+            Err(String::from("Invalid cache file")),
+            res.map_err(|e| e.to_string())
+        );
+    }
+}
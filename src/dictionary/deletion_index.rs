@@ -0,0 +1,170 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::io::{self, Write};
+
+use super::cache::{CacheError, Decode, Encode};
+use crate::util::Multimap;
+
+#[derive(Debug, Clone)]
+pub struct DeletionIndex<T>
+where
+    T: Clone + Eq + Hash,
+{
+    max_distance: usize,
+    deletions: HashMap<Vec<T>, Vec<usize>>,
+}
+
+pub fn deletions<T>(key: &[T], max_distance: usize) -> HashSet<Vec<T>>
+where
+    T: Clone + Eq + Hash,
+{
+    let mut res = HashSet::from([key.to_vec()]);
+    let mut level = Vec::from([key.to_vec()]);
+    for _ in 0..max_distance {
+        let mut next_level = Vec::new();
+        for k in level.iter() {
+            for i in 0..k.len() {
+                let mut deletion = k.clone();
+                deletion.remove(i);
+                if res.insert(deletion.clone()) {
+                    next_level.push(deletion);
+                }
+            }
+        }
+        level = next_level;
+    }
+    res
+}
+
+impl<T> DeletionIndex<T>
+where
+    T: Clone + Eq + Hash,
+{
+    pub fn new(max_distance: usize) -> Self {
+        Self {
+            max_distance,
+            deletions: HashMap::new(),
+        }
+    }
+
+    pub fn max_distance(&self) -> usize {
+        self.max_distance
+    }
+
+    pub fn len(&self) -> usize {
+        self.deletions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deletions.is_empty()
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = usize> + '_ {
+        self.deletions.values().flatten().copied()
+    }
+
+    pub fn insert(&mut self, item: usize, key: &[T]) {
+        for deletion in deletions(key, self.max_distance) {
+            self.deletions.mm_insert(deletion, item);
+        }
+    }
+
+    pub fn find(&self, key: &[T], max_distance: usize) -> Option<Vec<usize>> {
+        if max_distance > self.max_distance {
+            return None;
+        }
+        let mut res: Vec<usize> = deletions(key, max_distance)
+            .iter()
+            .filter_map(|d| self.deletions.get(d))
+            .flatten()
+            .copied()
+            .collect();
+        res.sort_unstable();
+        res.dedup();
+        Some(res)
+    }
+}
+
+impl<T> Encode for DeletionIndex<T>
+where
+    T: Clone + Eq + Hash + Encode,
+{
+    fn encode<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.max_distance.encode(w)?;
+        self.deletions.len().encode(w)?;
+        for (key, items) in self.deletions.iter() {
+            key.encode(w)?;
+            items.encode(w)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> Decode for DeletionIndex<T>
+where
+    T: Clone + Eq + Hash + Decode,
+{
+    fn decode(buf: &mut &[u8]) -> Result<Self, CacheError> {
+        let max_distance = usize::decode(buf)?;
+        let deletions: Vec<(Vec<T>, Vec<usize>)> = Vec::decode(buf)?;
+        Ok(Self {
+            max_distance,
+            deletions: deletions.into_iter().collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("", 2, &[""])]
+    #[case("ab", 0, &["ab"])]
+    #[case("ab", 1, &["a", "ab", "b"])]
+    #[case("ab", 2, &["", "a", "ab", "b"])]
+    #[case("aab", 1, &["aa", "aab", "ab"])]
+    #[case("ʃaj", 2, &["a", "aj", "j", "ʃ", "ʃa", "ʃaj", "ʃj"])]
+    fn test_deletions(
+        #[case] key: &str,
+        #[case] max_distance: usize,
+        #[case] expected: &[&str],
+    ) {
+        let key: Vec<char> = key.chars().collect();
+        let mut res: Vec<String> = deletions(&key, max_distance)
+            .into_iter()
+            .map(|d| d.into_iter().collect())
+            .collect();
+        res.sort();
+        assert_eq!(expected, res);
+    }
+
+    #[test]
+    fn test_deletion_index_init() {
+        let index = DeletionIndex::<char>::new(2);
+        assert_eq!(2, index.max_distance());
+        assert_eq!(0, index.len());
+        assert!(index.is_empty());
+        assert_eq!(Some(Vec::new()), index.find(&['x'], 1));
+    }
+
+    #[rstest]
+    #[case(&["kat", "bat", "bad", "skat", "at", "tak"], "kat", 0, Some(vec![0, 3]))]
+    #[case(&["kat", "bat", "bad", "skat", "at", "tak"], "kat", 1, Some(vec![0, 1, 3, 4]))]
+    #[case(&["kat", "bat", "bad", "skat", "at", "tak"], "kat", 2, Some(vec![0, 1, 2, 3, 4, 5]))]
+    #[case(&["kat", "bat", "bad", "skat", "at", "tak"], "kat", 3, None)]
+    fn test_deletion_index_find(
+        #[case] items: &[&str],
+        #[case] search: &str,
+        #[case] max_distance: usize,
+        #[case] expected: Option<Vec<usize>>,
+    ) {
+        let mut index = DeletionIndex::new(2);
+        for (i, item) in items.iter().enumerate() {
+            index.insert(i, &item.chars().collect::<Vec<char>>());
+        }
+        let search: Vec<char> = search.chars().collect();
+        assert_eq!(expected, index.find(&search, max_distance));
+    }
+}
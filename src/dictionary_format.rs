@@ -0,0 +1,299 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::arpabet::{arpabet_to_ipa, to_cmudict_pair};
+use crate::util::{to_header_pair, to_tsv_pair, tsv_fields};
+
+pub const COLUMNS_HEADER: &str = "columns";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DictionaryFormat {
+    Auto,
+    Tsv,
+    CmuDict,
+    IpaDict,
+    WikiPron,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Columns {
+    frequency: Option<usize>,
+    tags: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownDictionaryFormat {
+    format: String,
+}
+
+impl Display for UnknownDictionaryFormat {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown dictionary format {:?}", self.format)
+    }
+}
+
+impl std::error::Error for UnknownDictionaryFormat {}
+
+impl FromStr for DictionaryFormat {
+    type Err = UnknownDictionaryFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "tsv" => Ok(Self::Tsv),
+            "cmudict" => Ok(Self::CmuDict),
+            "ipa-dict" => Ok(Self::IpaDict),
+            "wikipron" => Ok(Self::WikiPron),
+            _ => Err(UnknownDictionaryFormat {
+                format: String::from(s),
+            }),
+        }
+    }
+}
+
+type Entry = (String, Vec<String>);
+
+impl Columns {
+    pub fn new() -> Self {
+        Self {
+            frequency: Some(2),
+            tags: Some(3),
+        }
+    }
+
+    pub fn from_names(names: &str) -> Self {
+        let names: Vec<String> = names
+            .split([',', '\t', ' '])
+            .filter(|n| !n.is_empty())
+            .map(|n| n.to_lowercase())
+            .collect();
+        let find = |aliases: &[&str]| {
+            names.iter().position(|n| aliases.contains(&&n[..]))
+        };
+        Self {
+            frequency: find(&["frequency", "freq", "count"]),
+            tags: find(&["pos", "tags", "tag"]),
+        }
+    }
+
+    pub fn parse(
+        &self,
+        line: &str,
+    ) -> Result<(Option<u64>, Vec<String>), String> {
+        let fields: Vec<&str> = tsv_fields(line).collect();
+        let field = |i: Option<usize>| {
+            i.and_then(|i| fields.get(i)).filter(|f| !f.is_empty())
+        };
+        let frequency =
+            match field(self.frequency) {
+                Some(f) => Some(f.parse().map_err(|_| {
+                    format!("Could not parse frequency {:?}", f)
+                })?),
+                None => None,
+            };
+        let tags = match field(self.tags) {
+            Some(t) => t
+                .split([',', '|', ' '])
+                .filter(|t| !t.is_empty())
+                .map(String::from)
+                .collect(),
+            None => Vec::new(),
+        };
+        Ok((frequency, tags))
+    }
+}
+
+impl Default for Columns {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn to_ipa_dict_entry(line: &str) -> Result<Option<Entry>, String> {
+    let (word, phonemes) = match to_tsv_pair(line)? {
+        Some(pair) => pair,
+        None => return Ok(None),
+    };
+    let variants: Vec<String> = phonemes
+        .split(',')
+        .map(|p| p.trim().trim_matches(['/', '[', ']']).trim())
+        .filter(|p| !p.is_empty())
+        .map(String::from)
+        .collect();
+    if variants.is_empty() {
+        return Err(format!("Could not parse transcription {:?}", phonemes));
+    }
+    Ok(Some((String::from(word), variants)))
+}
+
+fn to_wikipron_entry(line: &str) -> Result<Option<Entry>, String> {
+    Ok(to_tsv_pair(line)?.map(|(word, phones)| {
+        (
+            String::from(word),
+            vec![phones.split_whitespace().collect()],
+        )
+    }))
+}
+
+impl DictionaryFormat {
+    pub fn detect(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.starts_with(";;;") {
+            return Some(Self::CmuDict);
+        }
+        if line.is_empty() || to_header_pair(line).is_some() {
+            return None;
+        }
+        match line.split_once('\t') {
+            Some((_, phonemes)) => {
+                let phonemes = phonemes.split('\t').next().unwrap_or("").trim();
+                if phonemes.starts_with(['/', '[']) {
+                    Some(Self::IpaDict)
+                } else if phonemes.contains(char::is_whitespace) {
+                    Some(Self::WikiPron)
+                } else {
+                    Some(Self::Tsv)
+                }
+            },
+            None if line.starts_with('#') => None,
+            None => match line.split_once(char::is_whitespace) {
+                Some((_, phonemes)) if arpabet_to_ipa(phonemes).is_some() => {
+                    Some(Self::CmuDict)
+                },
+                _ => Some(Self::Tsv),
+            },
+        }
+    }
+
+    pub fn has_columns(&self) -> bool {
+        !matches!(self, Self::Auto | Self::CmuDict)
+    }
+
+    pub fn language(&self) -> Option<&'static str> {
+        match self {
+            Self::CmuDict => Some("en"),
+            _ => None,
+        }
+    }
+
+    pub fn parse_line(&self, line: &str) -> Result<Option<Entry>, String> {
+        match self {
+            Self::Auto => match Self::detect(line) {
+                Some(format) => format.parse_line(line),
+                None => Ok(None),
+            },
+            Self::Tsv => Ok(to_tsv_pair(line)?
+                .map(|(w, p)| (String::from(w), vec![String::from(p)]))),
+            Self::CmuDict => Ok(to_cmudict_pair(line)?
+                .map(|(w, p)| (w.to_lowercase(), vec![p]))),
+            Self::IpaDict => to_ipa_dict_entry(line),
+            Self::WikiPron => to_wikipron_entry(line),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("auto", Ok(DictionaryFormat::Auto))]
+    #[case("tsv", Ok(DictionaryFormat::Tsv))]
+    #[case("cmudict", Ok(DictionaryFormat::CmuDict))]
+    #[case("ipa-dict", Ok(DictionaryFormat::IpaDict))]
+    #[case("wikipron", Ok(DictionaryFormat::WikiPron))]
+    #[case("xx", Err(UnknownDictionaryFormat { format: String::from("xx") }))]
+    fn test_dictionary_format_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<DictionaryFormat, UnknownDictionaryFormat>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
+    #[rstest]
+    #[case(Columns::new(), "w\tp", Ok((None, &[][..])))]
+    #[case(Columns::new(), "w\tp\t12", Ok((Some(12), &[][..])))]
+    #[case(Columns::new(), "w\tp\t\tNOUN", Ok((None, &["NOUN"][..])))]
+    #[case(Columns::new(), "w\tp\t3\tNOUN,VERB", Ok((Some(3), &["NOUN", "VERB"][..])))]
+    #[case(Columns::new(), "w\tp\tx", Err("Could not parse frequency \"x\""))]
+    #[case(Columns::from_names("word phonemes pos"), "w\tp\tNOUN", Ok((None, &["NOUN"][..])))]
+    #[case(Columns::from_names("word, phonemes, tags, Freq"), "w\tp\tADJ\t7", Ok((Some(7), &["ADJ"][..])))]
+    #[case(Columns::from_names("word phonemes"), "w\tp\t3\tNOUN", Ok((None, &[][..])))]
+    fn test_columns_parse(
+        #[case] columns: Columns,
+        #[case] input: &str,
+        #[case] expected: Result<(Option<u64>, &[&str]), &str>,
+    ) {
+        assert_eq!(
+            expected
+                .map(|m| (m.0, m.1.iter().map(|t| String::from(*t)).collect()))
+                .map_err(String::from),
+            columns.parse(input)
+        );
+    }
+
+    #[rstest]
+    #[case("", None)]
+    #[case("# language: en", None)]
+    #[case("# comment", None)]
+    #[case("#tag\twɜːd", Some(DictionaryFormat::Tsv))]
+    #[case("word\twɜːd", Some(DictionaryFormat::Tsv))]
+    #[case("word\t/wɜːd/", Some(DictionaryFormat::IpaDict))]
+    #[case("word\t[wɜːd]", Some(DictionaryFormat::IpaDict))]
+    #[case("word\tw ɜː d", Some(DictionaryFormat::WikiPron))]
+    #[case("word\twɜːd\t12\tNOUN", Some(DictionaryFormat::Tsv))]
+    #[case(";;; comment", Some(DictionaryFormat::CmuDict))]
+    #[case("WORD  W ER1 D", Some(DictionaryFormat::CmuDict))]
+    #[case("word wɜːd", Some(DictionaryFormat::Tsv))]
+    fn test_dictionary_format_detect(
+        #[case] input: &str,
+        #[case] expected: Option<DictionaryFormat>,
+    ) {
+        assert_eq!(expected, DictionaryFormat::detect(input));
+    }
+
+    #[rstest]
+    #[case(DictionaryFormat::Tsv, "", Ok(None))]
+    #[case(DictionaryFormat::Tsv, "Word", Err("Could not parse line \"Word\""))]
+    #[case(
+        DictionaryFormat::Tsv,
+        "Word\t\tNOUN",
+        Err("Could not parse line \"Word\\t\\tNOUN\"")
+    )]
+    #[case(
+        DictionaryFormat::IpaDict,
+        "word\t/ /",
+        Err("Could not parse transcription \"/ /\"")
+    )]
+    #[case(DictionaryFormat::Tsv, "Word\twˈɜːd", Ok(Some(("Word", &["wˈɜːd"][..]))))]
+    #[case(DictionaryFormat::Tsv, "Word\t/wˈɜːd/", Ok(Some(("Word", &["/wˈɜːd/"][..]))))]
+    #[case(
+        DictionaryFormat::CmuDict,
+        "Word\twˈɜːd",
+        Err("Could not parse ARPAbet \"wˈɜːd\"")
+    )]
+    #[case(DictionaryFormat::CmuDict, "WORD(2)  W ER1 D", Ok(Some(("word", &["wˈɝd"][..]))))]
+    #[case(DictionaryFormat::IpaDict, "word\t/wˈɜːd/", Ok(Some(("word", &["wˈɜːd"][..]))))]
+    #[case(DictionaryFormat::IpaDict, "word\t/wˈɜːd/, /wˈɝd/", Ok(Some(("word", &["wˈɜːd", "wˈɝd"][..]))))]
+    #[case(DictionaryFormat::IpaDict, "word\t[wˈɜːd],[wˈɝd]", Ok(Some(("word", &["wˈɜːd", "wˈɝd"][..]))))]
+    #[case(DictionaryFormat::WikiPron, "word\tw ˈɜː d", Ok(Some(("word", &["wˈɜːd"][..]))))]
+    #[case(DictionaryFormat::WikiPron, "word\tt͡ʃ a", Ok(Some(("word", &["t͡ʃa"][..]))))]
+    #[case(DictionaryFormat::Auto, "word\t/wˈɜːd/, /wˈɝd/", Ok(Some(("word", &["wˈɜːd", "wˈɝd"][..]))))]
+    #[case(DictionaryFormat::Auto, "WORD  W ER1 D", Ok(Some(("word", &["wˈɝd"][..]))))]
+    fn test_dictionary_format_parse_line(
+        #[case] format: DictionaryFormat,
+        #[case] input: &str,
+        #[case] expected: Result<Option<(&str, &[&str])>, &str>,
+    ) {
+        assert_eq!(
+            expected
+                .map(|p| p.map(|p| (
+                    String::from(p.0),
+                    p.1.iter().map(|v| String::from(*v)).collect()
+                )))
+                .map_err(String::from),
+            format.parse_line(input)
+        );
+    }
+}
@@ -0,0 +1,598 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::phoneme::{is_modifier, is_tie_bar, Segment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Levenshtein,
+    Features,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Edit {
+    Match(Segment),
+    Substitute(Segment, Segment),
+    Insert(Segment),
+    Delete(Segment),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownMetric {
+    metric: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manner {
+    Plosive,
+    Nasal,
+    Trill,
+    Tap,
+    Fricative,
+    LateralFricative,
+    Affricate,
+    Approximant,
+    LateralApproximant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Features {
+    Consonant {
+        voiced: bool,
+        place: u8,
+        manner: Manner,
+    },
+    Vowel {
+        height: u8,
+        backness: u8,
+        rounded: bool,
+    },
+}
+
+pub const MIN_EDIT_COST: f64 = 0.125;
+
+const STEP_COST: f64 = 0.125;
+const VOICING_COST: f64 = 0.25;
+const MANNER_COST: f64 = 0.5;
+const MAX_PLACE_COST: f64 = 0.5;
+const ROUNDING_COST: f64 = 0.25;
+const WEAK_SEGMENT_COST: f64 = 0.5;
+const MODIFIER_COST: f64 = 0.25;
+
+const WEAK_SEGMENTS: &str = "əɚɐʔ";
+
+macro_rules! c {
+    ($voiced: expr, $place: expr, $manner: ident) => {
+        Features::Consonant {
+            voiced: $voiced,
+            place: $place,
+            manner: Manner::$manner,
+        }
+    };
+}
+
+macro_rules! v {
+    ($height: expr, $backness: expr, $rounded: expr) => {
+        Features::Vowel {
+            height: $height,
+            backness: $backness,
+            rounded: $rounded,
+        }
+    };
+}
+
+// place: bilabial (0) .. glottal (10)
+// height: close (0) .. open (6), backness: front (0) .. back (2)
+declare_static_array!(pub FEATURES, (char, Features), [
+    ('p', c!(false, 0, Plosive)),
+    ('b', c!(true, 0, Plosive)),
+    ('t', c!(false, 3, Plosive)),
+    ('d', c!(true, 3, Plosive)),
+    ('ʈ', c!(false, 5, Plosive)),
+    ('ɖ', c!(true, 5, Plosive)),
+    ('c', c!(false, 6, Plosive)),
+    ('ɟ', c!(true, 6, Plosive)),
+    ('k', c!(false, 7, Plosive)),
+    ('g', c!(true, 7, Plosive)),
+    ('ɡ', c!(true, 7, Plosive)),
+    ('q', c!(false, 8, Plosive)),
+    ('ɢ', c!(true, 8, Plosive)),
+    ('ʔ', c!(false, 10, Plosive)),
+    ('m', c!(true, 0, Nasal)),
+    ('ɱ', c!(true, 1, Nasal)),
+    ('n', c!(true, 3, Nasal)),
+    ('ɳ', c!(true, 5, Nasal)),
+    ('ɲ', c!(true, 6, Nasal)),
+    ('ŋ', c!(true, 7, Nasal)),
+    ('ɴ', c!(true, 8, Nasal)),
+    ('ʙ', c!(true, 0, Trill)),
+    ('r', c!(true, 3, Trill)),
+    ('ʀ', c!(true, 8, Trill)),
+    ('ⱱ', c!(true, 1, Tap)),
+    ('ɾ', c!(true, 3, Tap)),
+    ('ɽ', c!(true, 5, Tap)),
+    ('ɸ', c!(false, 0, Fricative)),
+    ('β', c!(true, 0, Fricative)),
+    ('f', c!(false, 1, Fricative)),
+    ('v', c!(true, 1, Fricative)),
+    ('θ', c!(false, 2, Fricative)),
+    ('ð', c!(true, 2, Fricative)),
+    ('s', c!(false, 3, Fricative)),
+    ('z', c!(true, 3, Fricative)),
+    ('ʃ', c!(false, 4, Fricative)),
+    ('ʒ', c!(true, 4, Fricative)),
+    ('ʂ', c!(false, 5, Fricative)),
+    ('ʐ', c!(true, 5, Fricative)),
+    ('ç', c!(false, 6, Fricative)),
+    ('ʝ', c!(true, 6, Fricative)),
+    ('x', c!(false, 7, Fricative)),
+    ('ɣ', c!(true, 7, Fricative)),
+    ('χ', c!(false, 8, Fricative)),
+    ('ʁ', c!(true, 8, Fricative)),
+    ('ħ', c!(false, 9, Fricative)),
+    ('ʕ', c!(true, 9, Fricative)),
+    ('h', c!(false, 10, Fricative)),
+    ('ɦ', c!(true, 10, Fricative)),
+    ('ɬ', c!(false, 3, LateralFricative)),
+    ('ɮ', c!(true, 3, LateralFricative)),
+    ('ʋ', c!(true, 1, Approximant)),
+    ('ɹ', c!(true, 3, Approximant)),
+    ('ɻ', c!(true, 5, Approximant)),
+    ('j', c!(true, 6, Approximant)),
+    ('ɥ', c!(true, 6, Approximant)),
+    ('ɰ', c!(true, 7, Approximant)),
+    ('w', c!(true, 7, Approximant)),
+    ('l', c!(true, 3, LateralApproximant)),
+    ('ɫ', c!(true, 3, LateralApproximant)),
+    ('ɭ', c!(true, 5, LateralApproximant)),
+    ('ʎ', c!(true, 6, LateralApproximant)),
+    ('ʟ', c!(true, 7, LateralApproximant)),
+    ('i', v!(0, 0, false)),
+    ('y', v!(0, 0, true)),
+    ('ɨ', v!(0, 1, false)),
+    ('ʉ', v!(0, 1, true)),
+    ('ɯ', v!(0, 2, false)),
+    ('u', v!(0, 2, true)),
+    ('ɪ', v!(1, 0, false)),
+    ('ʏ', v!(1, 0, true)),
+    ('ʊ', v!(1, 2, true)),
+    ('e', v!(2, 0, false)),
+    ('ø', v!(2, 0, true)),
+    ('ɘ', v!(2, 1, false)),
+    ('ɵ', v!(2, 1, true)),
+    ('ɤ', v!(2, 2, false)),
+    ('o', v!(2, 2, true)),
+    ('ə', v!(3, 1, false)),
+    ('ɚ', v!(3, 1, false)),
+    ('ɛ', v!(4, 0, false)),
+    ('œ', v!(4, 0, true)),
+    ('ɜ', v!(4, 1, false)),
+    ('ɞ', v!(4, 1, true)),
+    ('ʌ', v!(4, 2, false)),
+    ('ɔ', v!(4, 2, true)),
+    ('æ', v!(5, 0, false)),
+    ('ɐ', v!(5, 1, false)),
+    ('a', v!(6, 0, false)),
+    ('ɶ', v!(6, 0, true)),
+    ('ä', v!(6, 1, false)),
+    ('ɑ', v!(6, 2, false)),
+    ('ɒ', v!(6, 2, true))
+]);
+
+impl Display for Edit {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Match(s) => write!(f, "{}", s),
+            Self::Substitute(s, s2) => write!(f, "{}>{}", s, s2),
+            Self::Insert(s) => write!(f, "+{}", s),
+            Self::Delete(s) => write!(f, "-{}", s),
+        }
+    }
+}
+
+impl Display for UnknownMetric {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown distance metric {:?}", self.metric)
+    }
+}
+
+impl std::error::Error for UnknownMetric {}
+
+impl FromStr for Metric {
+    type Err = UnknownMetric;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "levenshtein" => Ok(Self::Levenshtein),
+            "features" => Ok(Self::Features),
+            _ => Err(UnknownMetric {
+                metric: String::from(s),
+            }),
+        }
+    }
+}
+
+impl Metric {
+    pub fn distance(&self, segments: &[Segment], segments2: &[Segment]) -> f64 {
+        match self {
+            Self::Levenshtein => levenshtein(segments, segments2) as f64,
+            Self::Features => feature_distance(segments, segments2),
+        }
+    }
+
+    pub fn align(
+        &self,
+        segments: &[Segment],
+        segments2: &[Segment],
+    ) -> Vec<Edit> {
+        match self {
+            Self::Levenshtein => align(
+                segments,
+                segments2,
+                |s, s2| if s == s2 { 0.0 } else { 1.0 },
+                |_| 1.0,
+            ),
+            Self::Features => {
+                align(segments, segments2, substitution_cost, indel_cost)
+            },
+        }
+    }
+
+    pub fn max_levenshtein_distance(&self, max_distance: f64) -> usize {
+        match self {
+            Self::Levenshtein => max_distance as usize,
+            Self::Features => (max_distance / MIN_EDIT_COST) as usize,
+        }
+    }
+}
+
+pub fn levenshtein<T: PartialEq>(items: &[T], items2: &[T]) -> usize {
+    let mut prev: Vec<usize> = (0..=items2.len()).collect();
+    let mut cur = vec![0; items2.len() + 1];
+    for (i, item) in items.iter().enumerate() {
+        cur[0] = i + 1;
+        for (j, item2) in items2.iter().enumerate() {
+            let substitution = prev[j] + usize::from(item != item2);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[items2.len()]
+}
+
+pub fn align<S, I>(
+    segments: &[Segment],
+    segments2: &[Segment],
+    substitution_cost: S,
+    indel_cost: I,
+) -> Vec<Edit>
+where
+    S: Fn(&Segment, &Segment) -> f64,
+    I: Fn(&Segment) -> f64,
+{
+    let width = segments2.len() + 1;
+    let mut costs = vec![0.0; (segments.len() + 1) * width];
+    for (j, s2) in segments2.iter().enumerate() {
+        costs[j + 1] = costs[j] + indel_cost(s2);
+    }
+    for (i, s) in segments.iter().enumerate() {
+        let row = (i + 1) * width;
+        costs[row] = costs[row - width] + indel_cost(s);
+        for (j, s2) in segments2.iter().enumerate() {
+            costs[row + j + 1] = (costs[row - width + j]
+                + substitution_cost(s, s2))
+            .min(costs[row - width + j + 1] + indel_cost(s))
+            .min(costs[row + j] + indel_cost(s2));
+        }
+    }
+
+    let mut res = Vec::with_capacity(segments.len().max(segments2.len()));
+    let (mut i, mut j) = (segments.len(), segments2.len());
+    while i > 0 || j > 0 {
+        let cost = costs[i * width + j];
+        if i > 0
+            && j > 0
+            && cost
+                == costs[(i - 1) * width + j - 1]
+                    + substitution_cost(&segments[i - 1], &segments2[j - 1])
+        {
+            i -= 1;
+            j -= 1;
+            res.push(if segments[i] == segments2[j] {
+                Edit::Match(segments[i].clone())
+            } else {
+                Edit::Substitute(segments[i].clone(), segments2[j].clone())
+            });
+        } else if i > 0
+            && cost == costs[(i - 1) * width + j] + indel_cost(&segments[i - 1])
+        {
+            i -= 1;
+            res.push(Edit::Delete(segments[i].clone()));
+        } else {
+            j -= 1;
+            res.push(Edit::Insert(segments2[j].clone()));
+        }
+    }
+    res.reverse();
+    res
+}
+
+pub fn features(c: char) -> Option<Features> {
+    FEATURES.iter().find(|f| f.0 == c).map(|f| f.1)
+}
+
+pub fn segment_features(segment: &Segment) -> Option<Features> {
+    let mut chars = segment.base().chars().filter(|c| !is_tie_bar(*c));
+    let first = features(chars.next()?)?;
+    let second = match chars.next() {
+        Some(c) => features(c)?,
+        None => return Some(first),
+    };
+    match (first, second) {
+        (
+            Features::Consonant {
+                manner: Manner::Plosive,
+                ..
+            },
+            Features::Consonant {
+                voiced,
+                place,
+                manner: Manner::Fricative | Manner::LateralFricative,
+            },
+        ) => Some(Features::Consonant {
+            voiced,
+            place,
+            manner: Manner::Affricate,
+        }),
+        (Features::Vowel { .. }, Features::Vowel { .. }) => Some(first),
+        _ => None,
+    }
+}
+
+pub fn indel_cost(segment: &Segment) -> f64 {
+    let base = segment.base();
+    if base.chars().all(is_modifier) {
+        MODIFIER_COST
+    } else if base.chars().all(|c| WEAK_SEGMENTS.contains(c)) {
+        WEAK_SEGMENT_COST
+    } else {
+        1.0
+    }
+}
+
+fn features_cost(f: Features, f2: Features) -> f64 {
+    let cost = match (f, f2) {
+        (
+            Features::Consonant {
+                voiced,
+                place,
+                manner,
+            },
+            Features::Consonant {
+                voiced: voiced2,
+                place: place2,
+                manner: manner2,
+            },
+        ) => {
+            let mut cost = (f64::from(place.abs_diff(place2)) * STEP_COST)
+                .min(MAX_PLACE_COST);
+            if voiced != voiced2 {
+                cost += VOICING_COST;
+            }
+            if manner != manner2 {
+                cost += MANNER_COST;
+            }
+            cost
+        },
+        (
+            Features::Vowel {
+                height,
+                backness,
+                rounded,
+            },
+            Features::Vowel {
+                height: height2,
+                backness: backness2,
+                rounded: rounded2,
+            },
+        ) => {
+            let steps = height.abs_diff(height2) + backness.abs_diff(backness2);
+            let mut cost = f64::from(steps) * STEP_COST;
+            if rounded != rounded2 {
+                cost += ROUNDING_COST;
+            }
+            cost
+        },
+        _ => 1.0,
+    };
+    cost.clamp(MIN_EDIT_COST, 1.0)
+}
+
+pub fn substitution_cost(segment: &Segment, segment2: &Segment) -> f64 {
+    if segment == segment2 {
+        return 0.0;
+    }
+    let mut cost = if segment.base() == segment2.base() {
+        0.0
+    } else {
+        match (segment_features(segment), segment_features(segment2)) {
+            (Some(f), Some(f2)) => features_cost(f, f2),
+            _ => 1.0,
+        }
+    };
+    if segment.modifiers() != segment2.modifiers() {
+        cost += MODIFIER_COST;
+    }
+    cost.clamp(MIN_EDIT_COST, 1.0)
+}
+
+pub fn feature_distance(segments: &[Segment], segments2: &[Segment]) -> f64 {
+    let mut prev: Vec<f64> = Vec::with_capacity(segments2.len() + 1);
+    prev.push(0.0);
+    for s2 in segments2.iter() {
+        prev.push(prev[prev.len() - 1] + indel_cost(s2));
+    }
+    let mut cur = vec![0.0; segments2.len() + 1];
+    for s in segments.iter() {
+        let delete = indel_cost(s);
+        cur[0] = prev[0] + delete;
+        for (j, s2) in segments2.iter().enumerate() {
+            cur[j + 1] = (prev[j] + substitution_cost(s, s2))
+                .min(prev[j + 1] + delete)
+                .min(cur[j] + indel_cost(s2));
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[segments2.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phoneme::segments;
+    use rstest::*;
+
+    #[rstest]
+    #[case("levenshtein", Ok(Metric::Levenshtein))]
+    #[case("features", Ok(Metric::Features))]
+    #[case("xx", Err(UnknownMetric { metric: String::from("xx") }))]
+    fn test_metric_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<Metric, UnknownMetric>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
+    #[test]
+    fn test_features_unique() {
+        for (i, f) in FEATURES.iter().enumerate() {
+            assert_eq!(None, FEATURES[i + 1..].iter().find(|f2| f.0 == f2.0));
+        }
+    }
+
+    #[rstest]
+    #[case("", "", 0)]
+    #[case("", "abc", 3)]
+    #[case("abc", "abc", 0)]
+    #[case("kitten", "sitting", 3)]
+    #[case("flaw", "lawn", 2)]
+    fn test_levenshtein(
+        #[case] input: &str,
+        #[case] input2: &str,
+        #[case] expected: usize,
+    ) {
+        let chars: Vec<char> = input.chars().collect();
+        let chars2: Vec<char> = input2.chars().collect();
+        assert_eq!(expected, levenshtein(&chars, &chars2));
+        assert_eq!(expected, levenshtein(&chars2, &chars));
+    }
+
+    #[rstest]
+    #[case("p", Some(c!(false, 0, Plosive)))]
+    #[case("tʃ", Some(c!(false, 4, Affricate)))]
+    #[case("d͡ʒ", Some(c!(true, 4, Affricate)))]
+    #[case("aɪ", Some(v!(6, 0, false)))]
+    #[case("ta", None)]
+    #[case("?", None)]
+    fn test_segment_features(
+        #[case] input: &str,
+        #[case] expected: Option<Features>,
+    ) {
+        assert_eq!(expected, segment_features(&Segment::new(input, "")));
+    }
+
+    #[rstest]
+    #[case(("p", ""), ("p", ""), 0.0)]
+    #[case(("p", ""), ("b", ""), 0.25)]
+    #[case(("t", ""), ("d", ""), 0.25)]
+    #[case(("p", ""), ("t", ""), 0.375)]
+    #[case(("p", ""), ("k", ""), 0.5)]
+    #[case(("p", ""), ("ʔ", ""), 0.5)]
+    #[case(("p", ""), ("m", ""), 0.75)]
+    #[case(("p", ""), ("a", ""), 1.0)]
+    #[case(("i", ""), ("ɪ", ""), 0.125)]
+    #[case(("i", ""), ("y", ""), 0.25)]
+    #[case(("i", ""), ("u", ""), 0.5)]
+    #[case(("i", ""), ("a", ""), 0.75)]
+    #[case(("ə", ""), ("?", ""), 1.0)]
+    #[case(("g", ""), ("ɡ", ""), 0.125)]
+    #[case(("tʃ", ""), ("ʃ", ""), 0.5)]
+    #[case(("tʃ", ""), ("dʒ", ""), 0.25)]
+    #[case(("a", "ː"), ("a", ""), 0.25)]
+    #[case(("a", "ː"), ("i", ""), 1.0)]
+    #[case(("n", "ʲ"), ("m", ""), 0.625)]
+    fn test_substitution_cost(
+        #[case] input: (&str, &str),
+        #[case] input2: (&str, &str),
+        #[case] expected: f64,
+    ) {
+        let s = Segment::new(input.0, input.1);
+        let s2 = Segment::new(input2.0, input2.1);
+        assert_eq!(expected, substitution_cost(&s, &s2));
+        assert_eq!(expected, substitution_cost(&s2, &s));
+    }
+
+    #[rstest]
+    #[case("", "", 0.0)]
+    #[case("", "pat", 3.0)]
+    #[case("pat", "pat", 0.0)]
+    #[case("pat", "bat", 0.25)]
+    #[case("pat", "bad", 0.5)]
+    #[case("pat", "pət", 0.5)]
+    #[case("pat", "pt", 1.0)]
+    #[case("pəat", "pat", 0.5)]
+    #[case("ˈpat", "pat", 0.0)]
+    #[case("paːt", "pat", 0.25)]
+    #[case("tʃap", "ʃap", 0.5)]
+    #[case("pat", "ʃiŋ", 2.75)]
+    fn test_feature_distance(
+        #[case] input: &str,
+        #[case] input2: &str,
+        #[case] expected: f64,
+    ) {
+        let input = segments(input);
+        let input2 = segments(input2);
+        assert_eq!(expected, feature_distance(&input, &input2));
+        assert_eq!(expected, feature_distance(&input2, &input));
+        assert_eq!(expected, Metric::Features.distance(&input, &input2));
+    }
+
+    #[rstest]
+    #[case(Metric::Levenshtein, "", "", "")]
+    #[case(Metric::Levenshtein, "pat", "pat", "p a t")]
+    #[case(Metric::Levenshtein, "pat", "bad", "p>b a t>d")]
+    #[case(Metric::Levenshtein, "pat", "pt", "p -a t")]
+    #[case(Metric::Levenshtein, "pt", "pat", "p +a t")]
+    #[case(Metric::Levenshtein, "", "ab", "+a +b")]
+    #[case(Metric::Features, "pat", "pəat", "p +ə a t")]
+    #[case(Metric::Features, "tʃap", "ʃapa", "tʃ>ʃ a p +a")]
+    #[case(Metric::Features, "paːt", "pat", "p aː>a t")]
+    fn test_metric_align(
+        #[case] metric: Metric,
+        #[case] input: &str,
+        #[case] input2: &str,
+        #[case] expected: &str,
+    ) {
+        let input = segments(input);
+        let input2 = segments(input2);
+        let res: Vec<String> = metric
+            .align(&input, &input2)
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        assert_eq!(expected, res.join(" "));
+    }
+
+    #[rstest]
+    #[case(Metric::Levenshtein, 0.0, 0)]
+    #[case(Metric::Levenshtein, 1.5, 1)]
+    #[case(Metric::Features, 0.0, 0)]
+    #[case(Metric::Features, 0.5, 4)]
+    fn test_metric_max_levenshtein_distance(
+        #[case] metric: Metric,
+        #[case] max_distance: f64,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(expected, metric.max_levenshtein_distance(max_distance));
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use crate::dictionary::{Dictionary, Word};
+use crate::distance::{levenshtein, Metric};
+use crate::word_groups::SourceFilter;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FalseFriend<'a> {
+    pub word: &'a Word,
+    pub source: usize,
+    pub word2: &'a Word,
+    pub source2: usize,
+    pub distance: f64,
+    pub spelling_distance: usize,
+}
+
+pub fn spelling_distance(word: &str, word2: &str) -> usize {
+    let chars: Vec<char> = word.to_lowercase().chars().collect();
+    let chars2: Vec<char> = word2.to_lowercase().chars().collect();
+    levenshtein(&chars, &chars2)
+}
+
+pub fn false_friends<'a>(
+    dicts: &[&'a Dictionary],
+    metric: Metric,
+    max_distance: f64,
+    min_spelling_distance: usize,
+    filter: &SourceFilter,
+) -> Vec<FalseFriend<'a>> {
+    let mut pairs = HashMap::<(&str, usize, &str, usize), FalseFriend>::new();
+    for (i, dict) in dicts.iter().enumerate() {
+        for (j, dict2) in dicts.iter().enumerate().skip(i + 1) {
+            if !filter.matches([i, j]) {
+                continue;
+            }
+            for word in dict.iter() {
+                for word2 in dict2.find_similar_by(word, metric, max_distance) {
+                    let spelling_distance =
+                        spelling_distance(&word.word, &word2.word);
+                    if spelling_distance < min_spelling_distance {
+                        continue;
+                    }
+                    let distance =
+                        metric.distance(&word.segments, &word2.segments);
+                    let key = (&word.word[..], i, &word2.word[..], j);
+                    match pairs.get(&key) {
+                        Some(f) if f.distance <= distance => (),
+                        _ => {
+                            pairs.insert(
+                                key,
+                                FalseFriend {
+                                    word,
+                                    source: i,
+                                    word2,
+                                    source2: j,
+                                    distance,
+                                    spelling_distance,
+                                },
+                            );
+                        },
+                    }
+                }
+            }
+        }
+    }
+    let mut res: Vec<FalseFriend> = pairs.into_values().collect();
+    res.sort_by(|f, f1| {
+        f.distance
+            .total_cmp(&f1.distance)
+            .then_with(|| f.word.word.cmp(&f1.word.word))
+            .then_with(|| f.word2.word.cmp(&f1.word2.word))
+            .then(f.source.cmp(&f1.source))
+            .then(f.source2.cmp(&f1.source2))
+    });
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("", "", 0)]
+    #[case("gift", "Gift", 0)]
+    #[case("gift", "gif", 1)]
+    #[case("chat", "шат", 4)]
+    fn test_spelling_distance(
+        #[case] word: &str,
+        #[case] word2: &str,
+        #[case] expected: usize,
+    ) {
+        assert_eq!(expected, spelling_distance(word, word2));
+    }
+
+    #[rstest]
+    #[case(0.0, 0, vec![("bad", "bat", 0.0, 1), ("gift", "Gift", 0.0, 0)])]
+    #[case(0.0, 1, vec![("bad", "bat", 0.0, 1)])]
+    #[case(1.0, 1, vec![("bad", "bat", 0.0, 1), ("bad", "bet", 1.0, 2), ("gift", "gif", 1.0, 1)])]
+    fn test_false_friends(
+        #[case] max_distance: f64,
+        #[case] min_spelling_distance: usize,
+        #[case] expected: Vec<(&str, &str, f64, usize)>,
+    ) {
+        let dict = Dictionary::from_entries(&[
+            ("bad", "bat"),
+            ("gift", "ɡɪft"),
+            ("zz", "zzz"),
+        ]);
+        let dict2 = Dictionary::from_entries(&[
+            ("bat", "bat"),
+            ("bet", "bɛt"),
+            ("Gift", "ɡɪft"),
+            ("gif", "ɡɪf"),
+        ]);
+        let res: Vec<(&str, &str, f64, usize)> = false_friends(
+            &[&dict, &dict2],
+            Metric::Levenshtein,
+            max_distance,
+            min_spelling_distance,
+            &SourceFilter::new(2),
+        )
+        .into_iter()
+        .map(|f| {
+            (
+                &f.word.word[..],
+                &f.word2.word[..],
+                f.distance,
+                f.spelling_distance,
+            )
+        })
+        .collect();
+        assert_eq!(expected, res);
+    }
+}
@@ -0,0 +1,303 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+
+use crate::word_groups::{GroupBuilder, Grouping, WordGroups};
+
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityGraph {
+    nodes: Vec<String>,
+    node_ids: HashMap<String, usize>,
+    edges: HashMap<(usize, usize), Option<f64>>,
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => res.push_str("&amp;"),
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '"' => res.push_str("&quot;"),
+            '\'' => res.push_str("&apos;"),
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+impl SimilarityGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn add_node(&mut self, word: &str) -> usize {
+        match self.node_ids.get(word) {
+            Some(id) => *id,
+            None => {
+                let id = self.nodes.len();
+                self.nodes.push(String::from(word));
+                self.node_ids.insert(String::from(word), id);
+                id
+            },
+        }
+    }
+
+    pub fn add_edge(&mut self, word: &str, word2: &str, distance: Option<f64>) {
+        let id = self.add_node(word);
+        let id2 = self.add_node(word2);
+        if id == id2 {
+            return;
+        }
+        let edge = self.edges.entry((id.min(id2), id.max(id2))).or_default();
+        *edge = match (*edge, distance) {
+            (Some(d), Some(d2)) => Some(d.min(d2)),
+            (d, d2) => d.or(d2),
+        };
+    }
+
+    fn sorted_edges(&self) -> Vec<((usize, usize), Option<f64>)> {
+        let mut res: Vec<((usize, usize), Option<f64>)> =
+            self.edges.iter().map(|(k, v)| (*k, *v)).collect();
+        res.sort_unstable_by_key(|e| e.0);
+        res
+    }
+
+    fn adjacency(&self) -> Vec<Vec<(usize, f64)>> {
+        let mut res = vec![Vec::new(); self.nodes.len()];
+        for ((id, id2), distance) in self.sorted_edges() {
+            let distance = distance.unwrap_or(1.0);
+            res[id].push((id2, distance));
+            res[id2].push((id, distance));
+        }
+        res
+    }
+
+    fn distances_from(
+        adjacency: &[Vec<(usize, f64)>],
+        id: usize,
+        max_distance: f64,
+    ) -> HashMap<usize, f64> {
+        let mut res = HashMap::from([(id, 0.0)]);
+        let mut queue = VecDeque::from([id]);
+        while let Some(id) = queue.pop_front() {
+            let distance = res[&id];
+            for (id2, d) in adjacency[id].iter() {
+                let d = distance + d;
+                if d <= max_distance && res.get(id2).is_none_or(|d2| d < *d2) {
+                    res.insert(*id2, d);
+                    queue.push_back(*id2);
+                }
+            }
+        }
+        res
+    }
+
+    fn connected_groups(&self) -> WordGroups {
+        let mut builder = GroupBuilder::<usize, String>::new();
+        for (id, id2) in self.edges.keys() {
+            builder.extend(*id, [&self.nodes[*id][..], &self.nodes[*id2][..]]);
+        }
+        builder.into_iter().collect()
+    }
+
+    pub fn groups(&self, grouping: Grouping) -> WordGroups {
+        if grouping == Grouping::Connected {
+            return self.connected_groups();
+        }
+        let adjacency = self.adjacency();
+        let mut seeds: Vec<usize> = (0..self.nodes.len()).collect();
+        seeds.sort_by_key(|id| Reverse(adjacency[*id].len()));
+        let mut grouped = vec![false; self.nodes.len()];
+        let mut res = Vec::new();
+        for seed in seeds {
+            if grouped[seed] {
+                continue;
+            }
+            grouped[seed] = true;
+            let mut candidates: Vec<(usize, f64)> = match grouping {
+                Grouping::Diameter(d) => {
+                    Self::distances_from(&adjacency, seed, d)
+                        .into_iter()
+                        .filter(|c| c.0 != seed)
+                        .collect()
+                },
+                _ => adjacency[seed].clone(),
+            };
+            candidates
+                .sort_by(|c, c1| c.1.total_cmp(&c1.1).then(c.0.cmp(&c1.0)));
+            let mut group = vec![seed];
+            for (id, _) in candidates {
+                if grouped[id] {
+                    continue;
+                }
+                let fits = match grouping {
+                    Grouping::Connected | Grouping::Star => true,
+                    Grouping::Complete => group
+                        .iter()
+                        .all(|m| adjacency[id].iter().any(|e| e.0 == *m)),
+                    Grouping::Diameter(d) => {
+                        let distances = Self::distances_from(&adjacency, id, d);
+                        group.iter().all(|m| distances.contains_key(m))
+                    },
+                };
+                if fits {
+                    grouped[id] = true;
+                    group.push(id);
+                }
+            }
+            res.push(group.into_iter().map(|id| self.nodes[id].clone()));
+        }
+        res.into_iter().collect()
+    }
+
+    pub fn write_dot<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "graph similar_words {{")?;
+        for node in self.nodes.iter() {
+            writeln!(out, "  \"{}\";", escape_dot(node))?;
+        }
+        for ((id, id2), distance) in self.sorted_edges() {
+            write!(
+                out,
+                "  \"{}\" -- \"{}\"",
+                escape_dot(&self.nodes[id]),
+                escape_dot(&self.nodes[id2])
+            )?;
+            match distance {
+                Some(d) => writeln!(out, " [weight={}, label=\"{}\"];", d, d)?,
+                None => writeln!(out, ";")?,
+            }
+        }
+        writeln!(out, "}}")
+    }
+
+    pub fn write_graphml<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+        writeln!(
+            out,
+            "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+        )?;
+        writeln!(
+            out,
+            "  <key id=\"word\" for=\"node\" attr.name=\"word\" attr.type=\"string\"/>"
+        )?;
+        writeln!(
+            out,
+            "  <key id=\"distance\" for=\"edge\" attr.name=\"distance\" attr.type=\"double\"/>"
+        )?;
+        writeln!(
+            out,
+            "  <graph id=\"similar_words\" edgedefault=\"undirected\">"
+        )?;
+        for (id, node) in self.nodes.iter().enumerate() {
+            writeln!(
+                out,
+                "    <node id=\"n{}\"><data key=\"word\">{}</data></node>",
+                id,
+                escape_xml(node)
+            )?;
+        }
+        for ((id, id2), distance) in self.sorted_edges() {
+            write!(out, "    <edge source=\"n{}\" target=\"n{}\"", id, id2)?;
+            match distance {
+                Some(d) => writeln!(
+                    out,
+                    "><data key=\"distance\">{}</data></edge>",
+                    d
+                )?,
+                None => writeln!(out, "/>")?,
+            }
+        }
+        writeln!(out, "  </graph>")?;
+        writeln!(out, "</graphml>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn graph(edges: &[(&str, &str, Option<f64>)]) -> SimilarityGraph {
+        let mut res = SimilarityGraph::new();
+        for (word, word2, distance) in edges {
+            res.add_edge(word, word2, *distance);
+        }
+        res
+    }
+
+    #[rstest]
+    #[case(&[], 0, 0)]
+    #[case(&[("a", "a", None)], 1, 0)]
+    #[case(&[("a", "b", Some(1.0)), ("b", "a", Some(2.0)), ("b", "c", None)], 3, 2)]
+    fn test_similarity_graph_add_edge(
+        #[case] edges: &[(&str, &str, Option<f64>)],
+        #[case] node_count: usize,
+        #[case] edge_count: usize,
+    ) {
+        let graph = graph(edges);
+        assert_eq!(node_count, graph.node_count());
+        assert_eq!(edge_count, graph.edge_count());
+    }
+
+    #[rstest]
+    #[case(Grouping::Connected, vec![vec!["bad", "bat", "bid", "cat"], vec!["x", "y"]])]
+    #[case(Grouping::Complete, vec![vec!["bad", "bid"], vec!["bat", "cat"], vec!["x", "y"]])]
+    #[case(Grouping::Star, vec![vec!["bad", "bat", "cat"], vec!["x", "y"]])]
+    #[case(Grouping::Diameter(1.0), vec![vec!["bad", "bid"], vec!["bat", "cat"], vec!["x", "y"]])]
+    #[case(Grouping::Diameter(2.0), vec![vec!["bad", "bat", "cat"], vec!["x", "y"]])]
+    #[case(Grouping::Diameter(3.0), vec![vec!["bad", "bat", "bid", "cat"], vec!["x", "y"]])]
+    fn test_similarity_graph_groups(
+        #[case] grouping: Grouping,
+        #[case] expected: Vec<Vec<&str>>,
+    ) {
+        let graph = graph(&[
+            ("cat", "bat", Some(1.0)),
+            ("bat", "bad", Some(1.0)),
+            ("bad", "bid", Some(1.0)),
+            ("x", "y", None),
+        ]);
+        let res: Vec<Vec<String>> =
+            graph.groups(grouping).into_iter().collect();
+        assert_eq!(expected, res);
+    }
+
+    #[rstest]
+    #[case(&[], "graph similar_words {\n}\n")]
+    #[case(&[("b", "a", Some(2.0)), ("a", "b", Some(0.5)), ("a", "c\"d", None)], "graph similar_words {\n  \"b\";\n  \"a\";\n  \"c\\\"d\";\n  \"b\" -- \"a\" [weight=0.5, label=\"0.5\"];\n  \"a\" -- \"c\\\"d\";\n}\n")]
+    fn test_similarity_graph_write_dot(
+        #[case] edges: &[(&str, &str, Option<f64>)],
+        #[case] expected: &str,
+    ) {
+        let mut out = Vec::new();
+        graph(edges).write_dot(&mut out).unwrap();
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn test_similarity_graph_write_graphml() {
+        let mut out = Vec::new();
+        graph(&[("a", "b&c", Some(1.0)), ("a", "d", None)])
+            .write_graphml(&mut out)
+            .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(
+            "<node id=\"n1\"><data key=\"word\">b&amp;c</data></node>"
+        ));
+        assert!(out.contains("<edge source=\"n0\" target=\"n1\"><data key=\"distance\">1</data></edge>"));
+        assert!(out.contains("<edge source=\"n0\" target=\"n2\"/>"));
+        assert!(out.ends_with("</graph>\n</graphml>\n"));
+    }
+}
@@ -6,6 +6,8 @@ pub struct Lines<'a, I> {
     file: &'a mut I,
     buf: String,
     eof: bool,
+    line_number: usize,
+    error: Option<io::Error>,
 }
 
 pub struct StrLines<'a> {
@@ -47,8 +49,22 @@ impl<'a, I: io::BufRead> Lines<'a, I> {
             file,
             buf: String::new(),
             eof: false,
+            line_number: 0,
+            error: None,
         }
     }
+
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    pub fn error(&self) -> Option<&io::Error> {
+        self.error.as_ref()
+    }
+
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
 }
 
 impl<'a, I: io::BufRead> StreamingIterator for Lines<'a, I> {
@@ -64,10 +80,13 @@ impl<'a, I: io::BufRead> StreamingIterator for Lines<'a, I> {
                 self.eof = n == 0;
             },
             Err(e) => {
-                eprintln!("Warning: failed to read line: {:?}", e);
+                self.error = Some(e);
                 self.eof = true;
             },
         }
+        if !self.eof || self.error.is_some() {
+            self.line_number += 1;
+        }
     }
 
     fn get(&self) -> Option<&Self::Item> {
@@ -148,6 +167,23 @@ mod tests {
         assert_eq!(expected, svec(lines(&mut Cursor::new(input))));
     }
 
+    #[test]
+    fn test_lines_error() {
+        let mut file = Cursor::new(b"xx\nyy\n\xff\nzz\n".to_vec());
+        let mut lines = lines(&mut file);
+        assert_eq!(Some("xx"), lines.next());
+        assert_eq!(1, lines.line_number());
+        assert_eq!(Some("yy"), lines.next());
+        assert_eq!(None, lines.next());
+        assert_eq!(3, lines.line_number());
+        assert_eq!(
+            io::ErrorKind::InvalidData,
+            lines.error().map(|e| e.kind()).unwrap()
+        );
+        assert!(lines.take_error().is_some());
+        assert!(lines.error().is_none());
+    }
+
     #[rstest]
     #[case("", &[])]
     #[case("\n\n", &["", ""])]
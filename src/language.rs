@@ -1,3 +1,5 @@
+use std::cmp::Reverse;
+
 use espeakng;
 
 #[derive(Debug, Clone)]
@@ -32,6 +34,10 @@ impl EspeakVoice {
     pub fn priority(&self) -> i8 {
         self.priority
     }
+
+    pub fn is(&self, name: &str) -> bool {
+        self.filename == name || self.filename.rsplit('/').next() == Some(name)
+    }
 }
 
 impl Language {
@@ -98,6 +104,17 @@ impl Languages {
         self.index(language).map(|i| &self.languages[i])
     }
 
+    pub fn get_by_voice(&self, voice: &str) -> Option<&Language> {
+        self.languages
+            .iter()
+            .filter_map(|l| {
+                let v = l.espeak_voices.iter().find(|v| v.is(voice))?;
+                Some((l, v.priority))
+            })
+            .min_by_key(|l| Reverse(l.1))
+            .map(|l| l.0)
+    }
+
     /*pub fn get_mut(&mut self, language: &str) -> Option<&mut Language> {
         self.index(language).map(|i| &mut self.languages[i])
     }
@@ -259,6 +276,35 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case("v1", Some("x"))]
+    #[case("v2", Some("y"))]
+    #[case("v3", Some("x"))]
+    #[case("v4", None)]
+    #[case("v5", Some("z"))]
+    #[case("dir/v5", Some("z"))]
+    fn test_languages_get_by_voice(
+        #[case] voice: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let mut ls = Languages::new();
+        for (language, filename, priority) in [
+            ("x", "v1", 5),
+            ("x", "v3", 1),
+            ("y", "v1", 2),
+            ("y", "v2", 5),
+            ("z", "v3", 1),
+            ("z", "dir/v5", 1),
+        ] {
+            ls.get_or_create_mut(language)
+                .add_espeak_voice(EspeakVoice {
+                    filename: String::from(filename),
+                    priority,
+                });
+        }
+        assert_eq!(expected, ls.get_by_voice(voice).map(|l| &l.name[..]));
+    }
+
     #[test]
     fn test_languages_get_supported() {
         let ls = Languages::get_supported();
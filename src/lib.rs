@@ -1,10 +1,20 @@
 #[macro_use]
 pub mod macros;
+pub mod arpabet;
 pub mod dictionary;
+pub mod dictionary_format;
+pub mod distance;
+pub mod false_friends;
+pub mod graph;
 pub mod iter;
 pub mod language;
 pub mod language_code;
 pub mod language_detection;
+pub mod minimal_pairs;
+pub mod normalization;
+pub mod output;
+pub mod parse_error;
 pub mod phoneme;
+pub mod syllable;
 pub mod util;
 pub mod word_groups;
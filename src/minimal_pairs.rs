@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::dictionary::{Dictionary, Word};
+use crate::phoneme::Segment;
+use crate::util::Multimap;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Contrast {
+    segment: String,
+    segment2: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidContrast {
+    contrast: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MinimalPair<'a> {
+    pub word: &'a Word,
+    pub word2: &'a Word,
+    pub source: usize,
+    pub contrast: Contrast,
+}
+
+impl Contrast {
+    pub fn new(segment: &str, segment2: &str) -> Self {
+        Self {
+            segment: String::from(segment.min(segment2)),
+            segment2: String::from(segment.max(segment2)),
+        }
+    }
+}
+
+impl Display for Contrast {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}~{}", self.segment, self.segment2)
+    }
+}
+
+impl Display for InvalidContrast {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Invalid contrast {:?}", self.contrast)
+    }
+}
+
+impl std::error::Error for InvalidContrast {}
+
+impl FromStr for Contrast {
+    type Err = InvalidContrast;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('~') {
+            Some((a, b)) if !a.is_empty() && !b.is_empty() && a != b => {
+                Ok(Self::new(a, b))
+            },
+            _ => Err(InvalidContrast {
+                contrast: String::from(s),
+            }),
+        }
+    }
+}
+
+pub fn contrast(
+    segments: &[Segment],
+    segments2: &[Segment],
+) -> Option<Contrast> {
+    if segments.len() != segments2.len() {
+        return None;
+    }
+    let mut diff = segments.iter().zip(segments2).filter(|(s, s2)| s != s2);
+    match (diff.next(), diff.next()) {
+        (Some((s, s2)), None) => Some(Contrast::new(s.as_str(), s2.as_str())),
+        _ => None,
+    }
+}
+
+pub fn minimal_pairs<'a>(dicts: &[&'a Dictionary]) -> Vec<MinimalPair<'a>> {
+    let mut buckets =
+        HashMap::<(usize, usize, Vec<&Segment>), Vec<&Word>>::new();
+    for (source, dict) in dicts.iter().enumerate() {
+        for word in dict.iter() {
+            for i in 0..word.segments.len() {
+                let rest = word
+                    .segments
+                    .iter()
+                    .enumerate()
+                    .filter(|s| s.0 != i)
+                    .map(|s| s.1)
+                    .collect();
+                buckets.mm_insert((source, i, rest), word);
+            }
+        }
+    }
+    let mut pairs =
+        HashMap::<(&str, &str, usize, Contrast), MinimalPair>::new();
+    for ((source, _, _), words) in buckets.iter() {
+        for (i, word) in words.iter().enumerate() {
+            for word2 in words.iter().skip(i + 1) {
+                if word.word == word2.word {
+                    continue;
+                }
+                let contrast = match contrast(&word.segments, &word2.segments) {
+                    Some(contrast) => contrast,
+                    None => continue,
+                };
+                let (word, word2) = if word.word < word2.word {
+                    (*word, *word2)
+                } else {
+                    (*word2, *word)
+                };
+                let key = (
+                    &word.word[..],
+                    &word2.word[..],
+                    *source,
+                    contrast.clone(),
+                );
+                pairs.entry(key).or_insert(MinimalPair {
+                    word,
+                    word2,
+                    source: *source,
+                    contrast,
+                });
+            }
+        }
+    }
+    let mut res: Vec<MinimalPair> = pairs.into_values().collect();
+    res.sort_by(|p, p1| {
+        p.contrast
+            .cmp(&p1.contrast)
+            .then_with(|| p.word.word.cmp(&p1.word.word))
+            .then_with(|| p.word2.word.cmp(&p1.word2.word))
+            .then(p.source.cmp(&p1.source))
+    });
+    res
+}
+
+pub fn group_by_contrast<'a>(
+    pairs: Vec<MinimalPair<'a>>,
+) -> Vec<(Contrast, Vec<MinimalPair<'a>>)> {
+    let mut groups = HashMap::<Contrast, Vec<MinimalPair>>::new();
+    for pair in pairs {
+        groups.mm_insert(pair.contrast.clone(), pair);
+    }
+    let mut res: Vec<(Contrast, Vec<MinimalPair>)> =
+        groups.into_iter().collect();
+    res.sort_by(|g, g1| g1.1.len().cmp(&g.1.len()).then(g.0.cmp(&g1.0)));
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::phoneme::segments;
+    use rstest::*;
+
+    #[rstest]
+    #[case("p~b", Ok(Contrast::new("b", "p")))]
+    #[case("ɪ~iː", Ok(Contrast::new("iː", "ɪ")))]
+    #[case("p", Err(InvalidContrast { contrast: String::from("p") }))]
+    #[case("p~", Err(InvalidContrast { contrast: String::from("p~") }))]
+    #[case("p~p", Err(InvalidContrast { contrast: String::from("p~p") }))]
+    fn test_contrast_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<Contrast, InvalidContrast>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
+    #[rstest]
+    #[case("pat", "bat", Some("b~p"))]
+    #[case("bɪt", "biːt", Some("iː~ɪ"))]
+    #[case("pat", "pat", None)]
+    #[case("pat", "bad", None)]
+    #[case("pat", "pats", None)]
+    fn test_contrast(
+        #[case] phonemes: &str,
+        #[case] phonemes2: &str,
+        #[case] expected: Option<&str>,
+    ) {
+        let res = contrast(&segments(phonemes), &segments(phonemes2));
+        assert_eq!(expected, res.map(|c| c.to_string()).as_deref());
+    }
+
+    #[test]
+    fn test_minimal_pairs() {
+        let dict = Dictionary::from_entries(&[
+            ("pat", "pat"),
+            ("bat", "bat"),
+            ("bad", "bad"),
+            ("pin", "pɪn"),
+            ("bin", "bɪn"),
+            ("been", "biːn"),
+            ("pat", "pæt"),
+        ]);
+        let pairs = minimal_pairs(&[&dict]);
+        let res: Vec<(&str, &str, String)> = pairs
+            .iter()
+            .map(|p| {
+                (&p.word.word[..], &p.word2.word[..], p.contrast.to_string())
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("bat", "pat", String::from("b~p")),
+                ("bin", "pin", String::from("b~p")),
+                ("bad", "bat", String::from("d~t")),
+                ("been", "bin", String::from("iː~ɪ")),
+            ],
+            res
+        );
+        let groups: Vec<(String, usize)> = group_by_contrast(pairs)
+            .into_iter()
+            .map(|g| (g.0.to_string(), g.1.len()))
+            .collect();
+        assert_eq!(
+            vec![
+                (String::from("b~p"), 2),
+                (String::from("d~t"), 1),
+                (String::from("iː~ɪ"), 1)
+            ],
+            groups
+        );
+    }
+}
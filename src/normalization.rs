@@ -0,0 +1,396 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::path;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::phoneme::{
+    is_ipa_modifier, is_modifier, is_separator, is_stress_mark,
+};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NormalizationProfile {
+    classes: HashMap<String, String>,
+    max_symbol_len: usize,
+    strip_modifiers: bool,
+    strip_superscripts: bool,
+    strip_separators: bool,
+    dedup: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProfileParseError {
+    line: usize,
+    reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownProfile {
+    profile: String,
+}
+
+const STRICT_PROFILE: &str = "
+# typographic variants only
+!strip-separators
+
+g ɡ
+";
+
+const DEFAULT_PROFILE: &str = "
+!strip-modifiers
+!dedup
+
+a ä ɐ ɑ ʌ
+e æ ɛ œ ɜ
+i ɨ ɪ
+o ɔ ɒ ɵ ʊ
+u ʉ
+y ʏ ø
+ɘ ɤ ɞ ə ɯ
+
+b ʙ
+d ɖ ɟ
+f ɸ
+g ɢ
+j ʎ ʝ
+k q
+l ɭ ɫ ʟ
+m ɱ
+n ɳ ɲ ŋ ɴ
+r ɾ ɹ ɽ ɻ ʀ ʁ
+t ʈ c
+v β ʋ
+x ɣ χ ħ h ɦ
+θ ð
+ʃ ʂ ç
+ʒ ʐ
+ɰ ʕ
+";
+
+const AGGRESSIVE_PROFILE: &str = "
+!strip-modifiers
+!strip-superscripts
+!strip-separators
+!dedup
+
+a ä ɐ ɑ ʌ æ ɒ ɶ
+e ɛ œ ɜ ø ɘ ɤ ɞ ə ɚ ɯ
+i ɨ ɪ y ʏ
+o ɔ ɵ ʊ u ʉ
+
+p b ʙ
+t d ʈ ɖ c ɟ
+k g ɡ q ɢ
+f v ɸ β ʋ ⱱ
+θ ð
+s z
+ʃ ʒ ʂ ʐ ç ʝ ɕ ʑ
+tʃ dʒ tɕ dʑ ʈʂ ɖʐ
+x ɣ χ ʁ ħ ʕ h ɦ
+j ʎ ɥ
+w ɰ
+l ɭ ɫ ʟ ɬ ɮ
+m ɱ
+n ɳ ɲ ŋ ɴ
+r ɾ ɹ ɽ ɻ ʀ
+";
+
+const ENGLISH_PROFILE: &str = "
+!strip-modifiers
+!strip-superscripts
+!strip-separators
+!dedup
+
+ɑ ɒ a
+ɜ ɝ
+ə ɐ ɚ ᵻ
+e ɛ
+oʊ əʊ o
+aɪ ɑɪ
+aʊ ɑʊ
+
+g ɡ
+l ɫ
+r ɹ ɻ ɾ
+";
+
+declare_static_array!(pub PROFILES, (&'static str, &'static str), [
+    ("strict", STRICT_PROFILE),
+    ("default", DEFAULT_PROFILE),
+    ("aggressive", AGGRESSIVE_PROFILE)
+]);
+
+declare_static_array!(pub LANGUAGE_PROFILES, (&'static str, &'static str), [
+    ("en", ENGLISH_PROFILE),
+    ("ru", DEFAULT_PROFILE)
+]);
+
+impl Display for ProfileParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Error for ProfileParseError {}
+
+impl Display for UnknownProfile {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown normalization profile {:?}", self.profile)
+    }
+}
+
+impl Error for UnknownProfile {}
+
+impl NormalizationProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn builtin(name: &str) -> Result<Self, UnknownProfile> {
+        PROFILES
+            .iter()
+            .find(|p| p.0 == name)
+            .map(|p| p.1.parse().unwrap())
+            .ok_or_else(|| UnknownProfile {
+                profile: String::from(name),
+            })
+    }
+
+    pub fn default_profile() -> &'static Self {
+        static PROFILE: OnceLock<NormalizationProfile> = OnceLock::new();
+        PROFILE.get_or_init(|| DEFAULT_PROFILE.parse().unwrap())
+    }
+
+    pub fn for_language(language: &str) -> &'static Self {
+        static PROFILES: OnceLock<HashMap<&str, NormalizationProfile>> =
+            OnceLock::new();
+        let code = language.split(['-', '_']).next().unwrap_or("");
+        PROFILES
+            .get_or_init(|| {
+                LANGUAGE_PROFILES
+                    .iter()
+                    .map(|p| (p.0, p.1.parse().unwrap()))
+                    .collect()
+            })
+            .get(code)
+            .unwrap_or_else(|| Self::default_profile())
+    }
+
+    pub fn from_file<P: AsRef<path::Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn Error>> {
+        Ok(fs::read_to_string(path)?.parse()?)
+    }
+
+    pub fn from_name_or_file(name: &str) -> Result<Self, Box<dyn Error>> {
+        match Self::builtin(name) {
+            Ok(profile) => Ok(profile),
+            Err(_) => Self::from_file(name),
+        }
+    }
+
+    pub fn add_class<'a, I>(
+        &mut self,
+        symbol: &str,
+        class: I,
+    ) -> Result<(), String>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        for member in class {
+            let prev = self
+                .classes
+                .insert(String::from(member), String::from(symbol));
+            if prev.is_some_and(|p| p != symbol) {
+                return Err(format!(
+                    "{:?} is already in another class",
+                    member
+                ));
+            }
+            self.max_symbol_len =
+                self.max_symbol_len.max(member.chars().count());
+        }
+        Ok(())
+    }
+
+    fn map<'a>(&'a self, phonemes: &'a str) -> (&'a str, usize) {
+        let ends = phonemes
+            .char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .take(self.max_symbol_len.max(1))
+            .collect::<Vec<usize>>();
+        for end in ends.iter().rev() {
+            if let Some(symbol) = self.classes.get(&phonemes[..*end]) {
+                return (symbol, *end);
+            }
+        }
+        (&phonemes[..ends[0]], ends[0])
+    }
+
+    fn is_stripped(&self, c: char) -> bool {
+        c.is_whitespace()
+            || is_stress_mark(c)
+            || (self.strip_separators && is_separator(c))
+            || (self.strip_modifiers && is_ipa_modifier(c))
+            || (self.strip_superscripts && is_modifier(c))
+    }
+
+    pub fn normalize(&self, phonemes: &str) -> String {
+        let filtered: String =
+            phonemes.chars().filter(|c| !self.is_stripped(*c)).collect();
+        let mut res = String::with_capacity(filtered.len());
+        let mut prev = "";
+        let mut rest = &filtered[..];
+        while !rest.is_empty() {
+            let (symbol, len) = self.map(rest);
+            if !(self.dedup && symbol == prev) {
+                res.push_str(symbol);
+            }
+            prev = symbol;
+            rest = &rest[len..];
+        }
+        res
+    }
+}
+
+impl FromStr for NormalizationProfile {
+    type Err = ProfileParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut res = Self::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let error = |reason: String| ProfileParseError {
+                line: i + 1,
+                reason,
+            };
+            match line.strip_prefix('!') {
+                Some("strip-modifiers") => res.strip_modifiers = true,
+                Some("strip-superscripts") => res.strip_superscripts = true,
+                Some("strip-separators") => res.strip_separators = true,
+                Some("dedup") => res.dedup = true,
+                Some(option) => {
+                    return Err(error(format!("Unknown option {:?}", option)))
+                },
+                None => {
+                    let mut class = line.split_whitespace();
+                    if let Some(symbol) = class.next() {
+                        res.add_class(symbol, class).map_err(error)?;
+                    }
+                },
+            }
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn test_builtin_profiles() {
+        for (name, _) in PROFILES.iter() {
+            assert!(NormalizationProfile::builtin(name).is_ok());
+        }
+        assert_eq!(
+            Err(UnknownProfile {
+                profile: String::from("xx")
+            }),
+            NormalizationProfile::builtin("xx")
+        );
+        assert_eq!(
+            &NormalizationProfile::builtin("default").unwrap(),
+            NormalizationProfile::default_profile()
+        );
+        for (language, _) in LANGUAGE_PROFILES.iter() {
+            assert!(!NormalizationProfile::for_language(language)
+                .classes
+                .is_empty());
+        }
+    }
+
+    #[rstest]
+    #[case("", "default")]
+    #[case("xx", "default")]
+    #[case("ru", "default")]
+    #[case("en", "en")]
+    #[case("en-us", "en")]
+    #[case("en_GB", "en")]
+    fn test_language_profile(#[case] language: &str, #[case] expected: &str) {
+        let expected = match expected {
+            "en" => ENGLISH_PROFILE.parse().unwrap(),
+            _ => NormalizationProfile::builtin(expected).unwrap(),
+        };
+        assert_eq!(&expected, NormalizationProfile::for_language(language));
+    }
+
+    #[rstest]
+    #[case("", "pˈaːtʲ", "paːtʲ")]
+    #[case("a b c", "abcd", "aaad")]
+    #[case("x ab", "aabab", "axx")]
+    #[case("!dedup\nx ab", "aabab", "ax")]
+    #[case("!strip-modifiers\n#comment\n\nx ab # comment", "aːbʲab", "xx")]
+    #[case("!strip-modifiers", "a.bᵝ", "a.bᵝ")]
+    #[case("!strip-superscripts\n!strip-separators", "a.bᵝ", "ab")]
+    fn test_profile_normalize(
+        #[case] profile: &str,
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) {
+        let profile: NormalizationProfile = profile.parse().unwrap();
+        assert_eq!(expected, profile.normalize(input));
+    }
+
+    #[rstest]
+    #[case("!xx", 1, "Unknown option \"xx\"")]
+    #[case("a b\n\nc b", 3, "\"b\" is already in another class")]
+    fn test_profile_parse_error(
+        #[case] profile: &str,
+        #[case] line: usize,
+        #[case] reason: &str,
+    ) {
+        assert_eq!(
+            Err(ProfileParseError {
+                line,
+                reason: String::from(reason)
+            }),
+            profile.parse::<NormalizationProfile>()
+        );
+    }
+
+    #[rstest]
+    #[case("strict", "ˈtʃɜːɡ", "tʃɜːg")]
+    #[case("default", "ˈtʃɜːɡ", "tʃeɡ")]
+    #[case("aggressive", "ˈtʃɜːɡ", "tʃek")]
+    #[case("aggressive", "ˈdʒɪb", "tʃip")]
+    fn test_builtin_profile_normalize(
+        #[case] profile: &str,
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) {
+        let profile = NormalizationProfile::builtin(profile).unwrap();
+        assert_eq!(expected, profile.normalize(input));
+    }
+
+    // `default` must keep the output of the original `-n` normalization
+    #[rstest]
+    #[case("", "")]
+    #[case("        ", "")]
+    #[case("ʌbɹiˌviejˈʃʌnz", "abriviejʃanz")]
+    #[case("m ʌ nʲ ɪ t o rʲ ɪ n k", "manitorink")]
+    #[case("ˈɡɑːʌd", "ɡad")]
+    #[case("ka.ta|ta‿t", "ka.ta|ta‿t")]
+    #[case("kʷaᵝⁿ", "kaᵝⁿ")]
+    #[case("t͡ʃʃəɯ", "tʃɘ")]
+    fn test_default_profile_baseline(
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) {
+        let profile = NormalizationProfile::default_profile();
+        assert_eq!(expected, profile.normalize(input));
+    }
+}
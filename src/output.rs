@@ -0,0 +1,307 @@
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::dictionary::Word;
+use crate::distance::Metric;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    JsonLines,
+    Csv,
+    Tsv,
+    Dot,
+    GraphMl,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownOutputFormat {
+    format: String,
+}
+
+impl Display for UnknownOutputFormat {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown output format {:?}", self.format)
+    }
+}
+
+impl std::error::Error for UnknownOutputFormat {}
+
+impl FromStr for OutputFormat {
+    type Err = UnknownOutputFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Self::Text),
+            "jsonl" => Ok(Self::JsonLines),
+            "csv" => Ok(Self::Csv),
+            "tsv" => Ok(Self::Tsv),
+            "dot" => Ok(Self::Dot),
+            "graphml" => Ok(Self::GraphMl),
+            _ => Err(UnknownOutputFormat {
+                format: String::from(s),
+            }),
+        }
+    }
+}
+
+impl OutputFormat {
+    pub fn delimiter(&self) -> char {
+        match self {
+            Self::Tsv => '\t',
+            _ => ',',
+        }
+    }
+
+    pub fn is_graph(&self) -> bool {
+        matches!(self, Self::Dot | Self::GraphMl)
+    }
+}
+
+pub const PAIR_HEADER: &[&str] = &[
+    "word",
+    "phonemes",
+    "source",
+    "match",
+    "match_phonemes",
+    "match_source",
+    "distance",
+];
+
+pub const FALSE_FRIEND_HEADER: &[&str] = &[
+    "word",
+    "phonemes",
+    "source",
+    "match",
+    "match_phonemes",
+    "match_source",
+    "distance",
+    "spelling_distance",
+];
+
+pub const MINIMAL_PAIR_HEADER: &[&str] = &[
+    "contrast",
+    "count",
+    "word",
+    "phonemes",
+    "match",
+    "match_phonemes",
+    "source",
+];
+
+pub fn write_json_string<W: Write>(out: &mut W, s: &str) -> io::Result<()> {
+    write!(out, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(out, "\\\"")?,
+            '\\' => write!(out, "\\\\")?,
+            '\n' => write!(out, "\\n")?,
+            '\r' => write!(out, "\\r")?,
+            '\t' => write!(out, "\\t")?,
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    write!(out, "\"")
+}
+
+pub fn write_jsonl_group<W: Write>(
+    out: &mut W,
+    words: &[(&Word, &str)],
+    metric: Metric,
+) -> io::Result<()> {
+    write!(out, "{{\"words\":[")?;
+    for (i, (word, source)) in words.iter().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{{\"word\":")?;
+        write_json_string(out, &word.word)?;
+        write!(out, ",\"phonemes\":")?;
+        write_json_string(out, &word.phonemes)?;
+        write!(out, ",\"source\":")?;
+        write_json_string(out, source)?;
+        write!(out, "}}")?;
+    }
+    write!(out, "],\"distances\":[")?;
+    let mut first = true;
+    for (i, (word, _)) in words.iter().enumerate() {
+        for (j, (word2, _)) in words.iter().enumerate().skip(i + 1) {
+            if !first {
+                write!(out, ",")?;
+            }
+            first = false;
+            let d = metric.distance(&word.segments, &word2.segments);
+            write!(out, "[{},{},{}]", i, j, d)?;
+        }
+    }
+    writeln!(out, "]}}")
+}
+
+pub fn write_jsonl_contrast<W: Write>(
+    out: &mut W,
+    contrast: &str,
+    pairs: &[(&Word, &Word, &str)],
+) -> io::Result<()> {
+    write!(out, "{{\"contrast\":")?;
+    write_json_string(out, contrast)?;
+    write!(out, ",\"count\":{},\"pairs\":[", pairs.len())?;
+    for (i, (word, word2, source)) in pairs.iter().enumerate() {
+        if i > 0 {
+            write!(out, ",")?;
+        }
+        write!(out, "{{\"word\":")?;
+        write_json_string(out, &word.word)?;
+        write!(out, ",\"phonemes\":")?;
+        write_json_string(out, &word.phonemes)?;
+        write!(out, ",\"match\":")?;
+        write_json_string(out, &word2.word)?;
+        write!(out, ",\"match_phonemes\":")?;
+        write_json_string(out, &word2.phonemes)?;
+        write!(out, ",\"source\":")?;
+        write_json_string(out, source)?;
+        write!(out, "}}")?;
+    }
+    writeln!(out, "]}}")
+}
+
+pub fn write_csv_row<W: Write, S: AsRef<str>>(
+    out: &mut W,
+    fields: &[S],
+    delimiter: char,
+) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            write!(out, "{}", delimiter)?;
+        }
+        let field = field.as_ref();
+        if field.contains(|c: char| c == delimiter || "\"\r\n".contains(c)) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            write!(out, "{}", field)?;
+        }
+    }
+    writeln!(out)
+}
+
+pub fn write_csv_pair<W: Write>(
+    out: &mut W,
+    (word, source): (&Word, &str),
+    (word2, source2): (&Word, &str),
+    distance: f64,
+    delimiter: char,
+) -> io::Result<()> {
+    write_csv_row(
+        out,
+        &[
+            &word.word[..],
+            &word.phonemes[..],
+            source,
+            &word2.word[..],
+            &word2.phonemes[..],
+            source2,
+            &distance.to_string()[..],
+        ],
+        delimiter,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("text", Ok(OutputFormat::Text))]
+    #[case("jsonl", Ok(OutputFormat::JsonLines))]
+    #[case("csv", Ok(OutputFormat::Csv))]
+    #[case("tsv", Ok(OutputFormat::Tsv))]
+    #[case("dot", Ok(OutputFormat::Dot))]
+    #[case("graphml", Ok(OutputFormat::GraphMl))]
+    #[case("xx", Err(UnknownOutputFormat { format: String::from("xx") }))]
+    fn test_output_format_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<OutputFormat, UnknownOutputFormat>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
+    #[rstest]
+    #[case("", "\"\"")]
+    #[case("ice cream", "\"ice cream\"")]
+    #[case("a\"b\\c", "\"a\\\"b\\\\c\"")]
+    #[case("a\tb\n\u{1}", "\"a\\tb\\n\\u0001\"")]
+    #[case("wɜːd", "\"wɜːd\"")]
+    fn test_write_json_string(#[case] input: &str, #[case] expected: &str) {
+        let mut out = Vec::new();
+        write_json_string(&mut out, input).unwrap();
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+
+    #[rstest]
+    #[case(&[], "{\"words\":[],\"distances\":[]}\n")]
+    #[case(&[("a b", "pa", "x")], "{\"words\":[{\"word\":\"a b\",\"phonemes\":\"pa\",\"source\":\"x\"}],\"distances\":[]}\n")]
+    #[case(&[("a", "pa", "x"), ("b", "ba", "y"), ("c", "bo", "y")], "{\"words\":[{\"word\":\"a\",\"phonemes\":\"pa\",\"source\":\"x\"},{\"word\":\"b\",\"phonemes\":\"ba\",\"source\":\"y\"},{\"word\":\"c\",\"phonemes\":\"bo\",\"source\":\"y\"}],\"distances\":[[0,1,1],[0,2,2],[1,2,1]]}\n")]
+    fn test_write_jsonl_group(
+        #[case] words: &[(&str, &str, &str)],
+        #[case] expected: &str,
+    ) {
+        let entries: Vec<Word> =
+            words.iter().map(|(w, p, _)| Word::new(w, p)).collect();
+        let words: Vec<(&Word, &str)> = entries
+            .iter()
+            .zip(words.iter())
+            .map(|(w, (_, _, s))| (w, *s))
+            .collect();
+        let mut out = Vec::new();
+        write_jsonl_group(&mut out, &words, Metric::Levenshtein).unwrap();
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn test_write_jsonl_contrast() {
+        let word = Word::new("bat", "bat");
+        let word2 = Word::new("pat", "pat");
+        let mut out = Vec::new();
+        write_jsonl_contrast(&mut out, "b~p", &[(&word, &word2, "x")]).unwrap();
+        assert_eq!(
+            "{\"contrast\":\"b~p\",\"count\":1,\"pairs\":[{\"word\":\"bat\",\"phonemes\":\"bat\",\"match\":\"pat\",\"match_phonemes\":\"pat\",\"source\":\"x\"}]}\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case(&[], ',', "\n")]
+    #[case(&["a", "b c", ""], ',', "a,b c,\n")]
+    #[case(&["a,b", "c\"d", "e\nf"], ',', "\"a,b\",\"c\"\"d\",\"e\nf\"\n")]
+    #[case(&["a,b", "c\td"], '\t', "a,b\t\"c\td\"\n")]
+    fn test_write_csv_row(
+        #[case] fields: &[&str],
+        #[case] delimiter: char,
+        #[case] expected: &str,
+    ) {
+        let mut out = Vec::new();
+        write_csv_row(&mut out, fields, delimiter).unwrap();
+        assert_eq!(expected, String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn test_write_csv_pair() {
+        let word = Word::new("ice cream", "ajs kɹim");
+        let word2 = Word::new("I scream", "aj skɹim");
+        let mut out = Vec::new();
+        write_csv_pair(
+            &mut out,
+            (&word, "a.tsv"),
+            (&word2, "b, c.tsv"),
+            0.5,
+            ',',
+        )
+        .unwrap();
+        assert_eq!(
+            "ice cream,ajs kɹim,a.tsv,I scream,aj skɹim,\"b, c.tsv\",0.5\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub file: Option<String>,
+    pub line: usize,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseLog {
+    mode: ParseMode,
+    file: Option<String>,
+    warnings: Vec<ParseError>,
+}
+
+impl ParseError {
+    pub fn new(file: Option<&str>, line: usize, reason: &str) -> Self {
+        Self {
+            file: file.map(String::from),
+            line,
+            reason: String::from(reason),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.file.as_ref() {
+            Some(file) => write!(f, "{}:{}: {}", file, self.line, self.reason),
+            None => write!(f, "line {}: {}", self.line, self.reason),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl ParseLog {
+    pub fn new(mode: ParseMode, file: Option<&str>) -> Self {
+        Self {
+            mode,
+            file: file.map(String::from),
+            warnings: Vec::new(),
+        }
+    }
+
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    pub fn report(
+        &mut self,
+        line: usize,
+        reason: &str,
+    ) -> Result<(), ParseError> {
+        let error = ParseError::new(self.file(), line, reason);
+        match self.mode {
+            ParseMode::Strict => Err(error),
+            ParseMode::Lenient => {
+                self.warnings.push(error);
+                Ok(())
+            },
+        }
+    }
+
+    pub fn warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
+    pub fn into_warnings(self) -> Vec<ParseError> {
+        self.warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(Some("dict.tsv"), 3, "Missing tab", "dict.tsv:3: Missing tab")]
+    #[case(None, 1, "Missing tab", "line 1: Missing tab")]
+    fn test_parse_error_display(
+        #[case] file: Option<&str>,
+        #[case] line: usize,
+        #[case] reason: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(expected, ParseError::new(file, line, reason).to_string());
+    }
+
+    #[test]
+    fn test_parse_log_strict() {
+        let mut log = ParseLog::new(ParseMode::Strict, Some("xx"));
+        assert_eq!(ParseMode::Strict, log.mode());
+        assert_eq!(
+            Err(ParseError::new(Some("xx"), 2, "reason")),
+            log.report(2, "reason")
+        );
+        assert!(log.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_parse_log_lenient() {
+        let mut log = ParseLog::new(ParseMode::Lenient, None);
+        assert_eq!(Ok(()), log.report(2, "reason"));
+        assert_eq!(Ok(()), log.report(5, "reason 2"));
+        assert_eq!(
+            vec![
+                ParseError::new(None, 2, "reason"),
+                ParseError::new(None, 5, "reason 2")
+            ],
+            log.into_warnings()
+        );
+    }
+}
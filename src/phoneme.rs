@@ -1,11 +1,11 @@
 use std::ffi::{c_void, CStr};
+use std::fmt::{self, Display, Formatter};
 use std::os::raw::c_char;
 
 use espeakng::Speaker;
 use espeakng_sys::{espeakCHARS_UTF8, espeak_TextToPhonemes};
 
-use crate::iter::IteratorEx;
-//use crate::util::map_char;
+use crate::normalization::NormalizationProfile;
 
 pub struct TextToPhonemes<'a> {
     _speaker: &'a Speaker,
@@ -13,6 +13,12 @@ pub struct TextToPhonemes<'a> {
     buf: Vec<u8>,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Segment {
+    text: String,
+    base_len: usize,
+}
+
 impl<'a> TextToPhonemes<'a> {
     pub fn new(speaker: &'a Speaker, ascii: bool) -> Self {
         Self {
@@ -44,73 +50,106 @@ impl<'a> TextToPhonemes<'a> {
     }
 }
 
-pub fn normalize_phonemes(phonemes: &str) -> String {
-    phonemes
-        .chars()
-        .filter(|c| {
-            //dbg!("filter char {:?} ({})", *c, u32::from(*c));
-            !(IPA_MODIFIERS.contains(&u32::from(*c)) || c.is_whitespace())
-        })
-        .map(|c| match c {
-            'a' | 'ä' | 'ɐ' | 'ɑ' | 'ʌ' => 'a',
-            'e' | 'æ' | 'ɛ' | 'œ' | 'ɜ' => 'e',
-            'i' | 'ɨ' | 'ɪ' => 'i',
-            'o' | 'ɔ' | 'ɒ' | 'ɵ' | 'ʊ' => 'o',
-            'u' | 'ʉ' => 'u',
-            'y' | 'ʏ' | 'ø' => 'y',
-            'ɘ' | 'ɤ' | 'ɞ' | 'ə' | 'ɯ' => 'ɘ',
-            'b' | 'ʙ' => 'b',
-            'd' | 'ɖ' | 'ɟ' => 'd',
-            'f' | 'ɸ' => 'f',
-            'g' | 'ɢ' => 'g',
-            'j' | 'ʎ' | 'ʝ' => 'j',
-            'k' | 'q' => 'k',
-            'l' | 'ɭ' | 'ɫ' | 'ʟ' => 'l',
-            'm' | 'ɱ' => 'm',
-            'n' | 'ɳ' | 'ɲ' | 'ŋ' | 'ɴ' => 'n',
-            'r' | 'ɾ' | 'ɹ' | 'ɽ' | 'ɻ' | 'ʀ' | 'ʁ' => 'r',
-            't' | 'ʈ' | 'c' => 't',
-            'v' | 'β' | 'ʋ' => 'v',
-            'x' | 'ɣ' | 'χ' | 'ħ' | 'h' | 'ɦ' => 'x',
-            'θ' | 'ð' => 'θ',
-            'ʃ' | 'ʂ' | 'ç' => 'ʃ',
-            'ʒ' | 'ʐ' => 'ʒ',
-            'ɰ' | 'ʕ' => 'ɰ',
-            c => c,
-        })
-        .dedup()
-        .collect()
+impl Segment {
+    pub fn new(base: &str, modifiers: &str) -> Self {
+        Self {
+            text: String::from(base) + modifiers,
+            base_len: base.len(),
+        }
+    }
+
+    pub fn base(&self) -> &str {
+        &self.text[..self.base_len]
+    }
+
+    pub fn modifiers(&self) -> &str {
+        &self.text[self.base_len..]
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.text.fmt(f)
+    }
+}
+
+pub fn is_ipa_modifier(c: char) -> bool {
+    IPA_MODIFIERS.contains(&u32::from(c))
+}
+
+pub fn is_modifier(c: char) -> bool {
+    is_ipa_modifier(c) || SUPERSCRIPT_MODIFIERS.contains(&c) || c == 'ⁿ'
+}
+
+pub fn is_stress_mark(c: char) -> bool {
+    STRESS_MARKS.contains(c)
+}
+
+pub fn is_separator(c: char) -> bool {
+    c.is_whitespace() || is_stress_mark(c) || SEPARATORS.contains(c)
+}
+
+pub fn is_tie_bar(c: char) -> bool {
+    TIE_BARS.contains(c)
+}
+
+fn base_len(phonemes: &str) -> usize {
+    let mut len = COMPOUND_SEGMENTS
+        .iter()
+        .find(|s| phonemes.starts_with(*s))
+        .map(|s| s.len())
+        .or_else(|| phonemes.chars().next().map(char::len_utf8))
+        .unwrap_or(0);
+    let mut chars = phonemes[len..].chars();
+    while let Some(tie) = chars.next().filter(|c| is_tie_bar(*c)) {
+        len += tie.len_utf8();
+        match chars.next().filter(|c| !is_separator(*c)) {
+            Some(c) => len += c.len_utf8(),
+            None => break,
+        }
+    }
+    len
+}
+
+pub fn segments(phonemes: &str) -> Vec<Segment> {
+    let mut res = Vec::new();
+    let mut rest = phonemes.trim_start_matches(is_separator);
+    while !rest.is_empty() {
+        let base_len = base_len(rest);
+        let len = base_len
+            + rest[base_len..]
+                .chars()
+                .take_while(|c| is_modifier(*c) && !is_separator(*c))
+                .map(char::len_utf8)
+                .sum::<usize>();
+        res.push(Segment::new(&rest[..base_len], &rest[base_len..len]));
+        rest = rest[len..].trim_start_matches(is_separator);
+    }
+    res
+}
+
+pub fn normalize_phonemes(phonemes: &str, language: &str) -> String {
+    NormalizationProfile::for_language(language).normalize(phonemes)
 }
 
 const IPA_MODIFIERS: std::ops::Range<u32> = 688..880;
+const SUPERSCRIPT_MODIFIERS: std::ops::RangeInclusive<char> = 'ᴬ'..='ᶿ';
+const STRESS_MARKS: &str = "ˈˌ";
+const SEPARATORS: &str = ".|‖‿";
+const TIE_BARS: &str = "\u{361}\u{35c}";
 
-/*declare_static_array!(pub NORMALIZE_PHONEMES_CHAR_MAP, (&'static str, char), [
-    ("aäɐɑʌ", 'a'),
-    ("eæɛœɜ", 'e'),
-    ("iɨɪ", 'i'),
-    ("oɔɒɵʊ", 'o'),
-    ("uʉ", 'u'),
-    ("yʏø", 'y'),
-    ("ɘɤɞəɯ", 'ɘ'),
-
-    ("bʙ", 'b'),
-    ("dɖɟ", 'd'),
-    ("fɸ", 'f'),
-    ("gɢ", 'g'),
-    ("jʎʝ", 'j'),
-    ("kq", 'k'),
-    ("lɭɫʟ", 'l'),
-    ("mɱ", 'm'),
-    ("nɳɲŋɴ", 'n'),
-    ("rɾɹɽɻʀʁ", 'r'),
-    ("tʈc", 't'),
-    ("vβʋ", 'v'),
-    ("xɣχħhɦ", 'x'),
-    ("θð", 'θ'),
-    ("ʃʂç", 'ʃ'),
-    ("ʒʐ", 'ʒ'),
-    ("ɰʕ", 'ɰ')
-]);*/
+declare_static_array!(
+    COMPOUND_SEGMENTS,
+    &'static str,
+    [
+        "tʃ", "dʒ", "tɕ", "dʑ", "ʈʂ", "ɖʐ", "aɪ", "aʊ", "eɪ", "oʊ", "ɔɪ", "əʊ",
+        "ɪə", "eə", "ʊə"
+    ]
+);
 
 #[cfg(test)]
 mod tests {
@@ -118,11 +157,49 @@ mod tests {
     use rstest::*;
 
     #[rstest]
-    #[case("", "")]
-    #[case("        ", "")]
-    #[case("ʌbɹiˌviejˈʃʌnz", "abriviejʃanz")]
-    #[case("m ʌ nʲ ɪ t o rʲ ɪ n k", "manitorink")]
-    fn test_normalize_phonemes(#[case] input: &str, #[case] expected: &str) {
-        assert_eq!(expected, normalize_phonemes(input));
+    #[case("", "", "")]
+    #[case("", "        ", "")]
+    #[case("", "ʌbɹiˌviejˈʃʌnz", "abriviejʃanz")]
+    #[case("ru", "m ʌ nʲ ɪ t o rʲ ɪ n k", "manitorink")]
+    #[case("en", "ʌbɹiˌviejˈʃʌnz", "ʌbriviejʃʌnz")]
+    #[case("en", "bˈɪt", "bɪt")]
+    #[case("en", "bˈiːt", "bit")]
+    #[case("en-us", "wˈɔːɾɚ", "wɔrə")]
+    fn test_normalize_phonemes(
+        #[case] language: &str,
+        #[case] input: &str,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(expected, normalize_phonemes(input, language));
+    }
+
+    #[rstest]
+    #[case("t", "")]
+    #[case("tʃ", "")]
+    #[case("aː", "ː")]
+    #[case("nʲ", "ʲ")]
+    fn test_segment(#[case] base: &str, #[case] modifiers: &str) {
+        let s = Segment::new(base, modifiers);
+        assert_eq!(base, s.base());
+        assert_eq!(modifiers, s.modifiers());
+        assert_eq!(String::from(base) + modifiers, s.as_str());
+        assert_eq!(s.as_str(), s.to_string());
+    }
+
+    #[rstest]
+    #[case("", &[])]
+    #[case("  ˈ. ", &[])]
+    #[case("kat", &[("k", ""), ("a", ""), ("t", "")])]
+    #[case("ˈtʃɜːtʃ", &[("tʃ", ""), ("ɜ", "ː"), ("tʃ", "")])]
+    #[case("dʒˈaɪənt", &[("dʒ", ""), ("aɪ", ""), ("ə", ""), ("n", ""), ("t", "")])]
+    #[case("t͡sa", &[("t͡s", ""), ("a", "")])]
+    #[case("t͡", &[("t͡", "")])]
+    #[case("m ʌ nʲ ɪ", &[("m", ""), ("ʌ", ""), ("n", "ʲ"), ("ɪ", "")])]
+    #[case("pʰa\u{303}ː", &[("p", "ʰ"), ("a", "\u{303}ː")])]
+    #[case("ːa", &[("ː", ""), ("a", "")])]
+    fn test_segments(#[case] input: &str, #[case] expected: &[(&str, &str)]) {
+        let expected: Vec<Segment> =
+            expected.iter().map(|(b, m)| Segment::new(b, m)).collect();
+        assert_eq!(expected, segments(input));
     }
 }
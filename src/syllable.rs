@@ -0,0 +1,174 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::distance::{segment_features, Features};
+use crate::phoneme::{segments, Segment};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordPart {
+    Rhyme,
+    Onset,
+    StressedSyllable,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownWordPart {
+    part: String,
+}
+
+const PRIMARY_STRESS: char = 'ˈ';
+
+impl Display for UnknownWordPart {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown word part {:?}", self.part)
+    }
+}
+
+impl std::error::Error for UnknownWordPart {}
+
+impl FromStr for WordPart {
+    type Err = UnknownWordPart;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rhyme" => Ok(Self::Rhyme),
+            "onset" => Ok(Self::Onset),
+            "stressed-syllable" => Ok(Self::StressedSyllable),
+            _ => Err(UnknownWordPart {
+                part: String::from(s),
+            }),
+        }
+    }
+}
+
+impl WordPart {
+    pub fn extract(&self, phonemes: &str) -> String {
+        match self {
+            Self::Rhyme => rhyme(phonemes),
+            Self::Onset => onset(phonemes),
+            Self::StressedSyllable => stressed_syllable(phonemes),
+        }
+    }
+}
+
+fn join(segments: &[Segment]) -> String {
+    segments.iter().map(Segment::as_str).collect()
+}
+
+pub fn is_vowel(segment: &Segment) -> bool {
+    matches!(segment_features(segment), Some(Features::Vowel { .. }))
+}
+
+pub fn rhyme(phonemes: &str) -> String {
+    let (stressed, rest) = match phonemes.rsplit_once(PRIMARY_STRESS) {
+        Some((_, rest)) => (true, rest),
+        None => (false, phonemes),
+    };
+    let segments = segments(rest);
+    let vowel = if stressed {
+        segments.iter().position(is_vowel)
+    } else {
+        segments.iter().rposition(is_vowel)
+    };
+    join(&segments[vowel.unwrap_or(0)..])
+}
+
+pub fn onset(phonemes: &str) -> String {
+    let segments = segments(phonemes);
+    let vowel = segments.iter().position(is_vowel);
+    join(&segments[..vowel.unwrap_or(segments.len())])
+}
+
+pub fn stressed_syllable(phonemes: &str) -> String {
+    let (prefix, rest) = phonemes
+        .split_once(PRIMARY_STRESS)
+        .unwrap_or(("", phonemes));
+    let prefix = segments(prefix);
+    let rest = segments(rest);
+    let start = match prefix.iter().rposition(is_vowel) {
+        _ if rest.first().is_some_and(|s| !is_vowel(s)) => prefix.len(),
+        Some(i) if prefix.len() - i > 2 => i + 2,
+        Some(i) => i + 1,
+        None => 0,
+    };
+    let end = match rest.iter().position(is_vowel) {
+        Some(i) => match rest[i + 1..].iter().position(is_vowel) {
+            Some(j) => (i + j).max(i + 1),
+            None => rest.len(),
+        },
+        None => rest.len(),
+    };
+    join(&prefix[start..]) + &join(&rest[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case("rhyme", Ok(WordPart::Rhyme))]
+    #[case("onset", Ok(WordPart::Onset))]
+    #[case("stressed-syllable", Ok(WordPart::StressedSyllable))]
+    #[case("xx", Err(UnknownWordPart { part: String::from("xx") }))]
+    fn test_word_part_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<WordPart, UnknownWordPart>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
+    #[rstest]
+    #[case("a", "", true)]
+    #[case("a", "ː", true)]
+    #[case("aɪ", "", true)]
+    #[case("t", "", false)]
+    #[case("tʃ", "", false)]
+    #[case("ʔ", "", false)]
+    fn test_is_vowel(
+        #[case] base: &str,
+        #[case] modifiers: &str,
+        #[case] expected: bool,
+    ) {
+        assert_eq!(expected, is_vowel(&Segment::new(base, modifiers)));
+    }
+
+    #[rstest]
+    #[case("", "")]
+    #[case("pst", "pst")]
+    #[case("kat", "at")]
+    #[case("kaˌtat", "at")]
+    #[case("bˈɪt", "ɪt")]
+    #[case("ˈtʃɜːtʃ", "ɜːtʃ")]
+    #[case("dʒˈaɪənt", "aɪənt")]
+    #[case("ʌbɹiˌviejˈʃʌnz", "ʌnz")]
+    #[case("m ˈʌ nʲ ɪ", "ʌnʲɪ")]
+    fn test_rhyme(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(expected, rhyme(input));
+    }
+
+    #[rstest]
+    #[case("", "")]
+    #[case("ˈaɪs", "")]
+    #[case("pst", "pst")]
+    #[case("stɹˈɒŋ", "stɹ")]
+    #[case("ˈtʃɜːtʃ", "tʃ")]
+    fn test_onset(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(expected, onset(input));
+    }
+
+    #[rstest]
+    #[case("", "")]
+    #[case("pst", "pst")]
+    #[case("kata", "ka")]
+    #[case("bəlˈoʊ", "loʊ")]
+    #[case("bˈɪt", "bɪt")]
+    #[case("ˈtʃɜːtʃ", "tʃɜːtʃ")]
+    #[case("dʒˈaɪənt", "dʒaɪ")]
+    #[case("mˈɪstɚ", "mɪs")]
+    #[case("ɪnstˈɔːl", "stɔːl")]
+    #[case("ˌaʊtˈsaɪd", "saɪd")]
+    fn test_stressed_syllable(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(expected, stressed_syllable(input));
+    }
+}
@@ -6,6 +6,26 @@ use std::io;
 use std::path;
 use std::process;
 
+use flate2::bufread::MultiGzDecoder;
+use flate2::write::GzEncoder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+// Call finish(), dropping the writer ignores write errors.
+pub enum Encoder<W: io::Write> {
+    None(io::BufWriter<W>),
+    Gzip(io::BufWriter<GzEncoder<W>>),
+    Zstd(io::BufWriter<zstd::Encoder<'static, W>>),
+}
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
 pub trait ArgParser
 where
     Self: Sized,
@@ -49,23 +69,124 @@ impl<K: Eq + Hash, V> Multimap<K, V> for HashMap<K, Vec<V>> {
     map.iter().find(|m| m.0.contains(c)).map(|m| m.1).unwrap_or(c)
 }*/
 
-pub fn to_tsv_pair(line: &str) -> Option<(&str, &str)> {
+pub fn to_tsv_pair(line: &str) -> Result<Option<(&str, &str)>, String> {
     let trimmed = line.trim();
     if let Some((key, value)) = trimmed.split_once('\t') {
         let key = key.trim();
-        let value = value.trim();
-        if !(key.is_empty() && value.is_empty()) {
-            return Some((key, value));
+        let value = value.split('\t').next().unwrap_or("").trim();
+        if !key.is_empty() && !value.is_empty() {
+            return Ok(Some((key, value)));
+        }
+    }
+    if trimmed.is_empty() {
+        Ok(None)
+    } else {
+        Err(format!("Could not parse line {:?}", trimmed))
+    }
+}
+
+pub fn tsv_fields(line: &str) -> impl Iterator<Item = &str> {
+    line.trim().split('\t').map(str::trim)
+}
+
+pub fn to_header_pair(line: &str) -> Option<(&str, &str)> {
+    let trimmed = line.trim();
+    let (key, value) = trimmed.strip_prefix('#')?.split_once(':')?;
+    let key = key.trim();
+    let value = value.trim();
+    if key.is_empty() || value.is_empty() || trimmed.contains('\t') {
+        None
+    } else {
+        Some((key, value))
+    }
+}
+
+impl Compression {
+    pub fn from_path<P: AsRef<path::Path>>(path: P) -> Self {
+        match path.as_ref().extension().and_then(|e| e.to_str()) {
+            Some("gz") => Self::Gzip,
+            Some("zst" | "zstd") => Self::Zstd,
+            _ => Self::None,
+        }
+    }
+
+    pub fn from_magic(bytes: &[u8]) -> Self {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Self::Zstd
+        } else {
+            Self::None
+        }
+    }
+}
+
+pub fn decompress<R: io::BufRead + 'static>(
+    mut reader: R,
+    compression: Compression,
+) -> io::Result<Box<dyn io::BufRead>> {
+    let compression = match compression {
+        Compression::None => Compression::from_magic(reader.fill_buf()?),
+        c => c,
+    };
+    match compression {
+        Compression::None => Ok(Box::new(reader)),
+        Compression::Gzip => {
+            Ok(Box::new(io::BufReader::new(MultiGzDecoder::new(reader))))
+        },
+        Compression::Zstd => Ok(Box::new(io::BufReader::new(
+            zstd::Decoder::with_buffer(reader)?,
+        ))),
+    }
+}
+
+impl<W: io::Write> Encoder<W> {
+    pub fn finish(self) -> io::Result<W> {
+        let mut res = match self {
+            Self::None(w) => w.into_inner().map_err(|e| e.into_error())?,
+            Self::Gzip(w) => {
+                w.into_inner().map_err(|e| e.into_error())?.finish()?
+            },
+            Self::Zstd(w) => {
+                w.into_inner().map_err(|e| e.into_error())?.finish()?
+            },
+        };
+        res.flush()?;
+        Ok(res)
+    }
+}
+
+impl<W: io::Write> io::Write for Encoder<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::None(w) => w.write(buf),
+            Self::Gzip(w) => w.write(buf),
+            Self::Zstd(w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::None(w) => w.flush(),
+            Self::Gzip(w) => w.flush(),
+            Self::Zstd(w) => w.flush(),
         }
     }
-    if !trimmed.is_empty() {
-        /*return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!("Could not parse line {:?}", trimmed)
-        ));*/
-        eprintln!("Warning: could not parse line {:?}", trimmed);
+}
+
+pub fn compress<W: io::Write>(
+    writer: W,
+    compression: Compression,
+) -> io::Result<Encoder<W>> {
+    match compression {
+        Compression::None => Ok(Encoder::None(io::BufWriter::new(writer))),
+        Compression::Gzip => Ok(Encoder::Gzip(io::BufWriter::new(
+            GzEncoder::new(writer, flate2::Compression::default()),
+        ))),
+        Compression::Zstd => Ok(Encoder::Zstd(io::BufWriter::new(
+            zstd::Encoder::new(writer, 0)?,
+        ))),
     }
-    None
 }
 
 pub fn open_input_file<P: AsRef<path::Path>>(
@@ -74,21 +195,21 @@ pub fn open_input_file<P: AsRef<path::Path>>(
     match path.as_ref() {
         Some(p) => {
             let fp = fs::File::open(p)?;
-            Ok(Box::new(io::BufReader::new(fp)))
+            decompress(io::BufReader::new(fp), Compression::from_path(p))
         },
-        None => Ok(Box::new(io::BufReader::new(io::stdin()))),
+        None => decompress(io::BufReader::new(io::stdin()), Compression::None),
     }
 }
 
 pub fn open_output_file<P: AsRef<path::Path>>(
     path: &Option<P>,
-) -> io::Result<Box<dyn io::Write>> {
+) -> io::Result<Encoder<Box<dyn io::Write>>> {
     match path.as_ref() {
         Some(p) => {
             let fp = fs::File::create(p)?;
-            Ok(Box::new(io::BufWriter::new(fp)))
+            compress(Box::new(fp), Compression::from_path(p))
         },
-        None => Ok(Box::new(io::stdout())),
+        None => compress(Box::new(io::stdout()), Compression::None),
     }
 }
 
@@ -97,6 +218,7 @@ mod tests {
     use super::*;
     use rstest::*;
     use std::collections::HashMap;
+    use std::io::{Read, Write};
 
     /*#[rstest]
     #[case('a', 'd')]
@@ -114,18 +236,49 @@ mod tests {
     #[case(" ")]
     #[case("\t")]
     #[case("\t \t ")]
-    #[case("xx\t \t ")]
-    #[case("\t \txx ")]
-    fn test_to_tsv_pair_invalid(#[case] input: &str) {
-        assert_eq!(None, to_tsv_pair(input));
+    fn test_to_tsv_pair_empty(#[case] input: &str) {
+        assert_eq!(Ok(None), to_tsv_pair(input));
+    }
+
+    #[rstest]
+    #[case("xx\t \t ", "Could not parse line \"xx\"")]
+    #[case("\t \txx ", "Could not parse line \"xx\"")]
+    #[case("xx yy", "Could not parse line \"xx yy\"")]
+    #[case("xx\t\tNOUN", "Could not parse line \"xx\\t\\tNOUN\"")]
+    fn test_to_tsv_pair_invalid(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(Err(String::from(expected)), to_tsv_pair(input));
     }
 
     #[rstest]
     #[case("xx\tyy", ("xx", "yy"))]
     #[case("  xx\tyy  \n", ("xx", "yy"))]
     #[case(" \t xx\tyy \t ", ("xx", "yy"))]
+    #[case("xx\tyy\t12\tNOUN", ("xx", "yy"))]
     fn test_to_tsv_pair(#[case] input: &str, #[case] expected: (&str, &str)) {
-        assert_eq!(Some(expected), to_tsv_pair(input));
+        assert_eq!(Ok(Some(expected)), to_tsv_pair(input));
+    }
+
+    #[rstest]
+    #[case("", &[""])]
+    #[case("xx\tyy", &["xx", "yy"])]
+    #[case(" xx \t yy\t\tNOUN\n", &["xx", "yy", "", "NOUN"])]
+    fn test_tsv_fields(#[case] input: &str, #[case] expected: &[&str]) {
+        assert_eq!(expected, tsv_fields(input).collect::<Vec<&str>>());
+    }
+
+    #[rstest]
+    #[case("", None)]
+    #[case("language: en", None)]
+    #[case("#", None)]
+    #[case("# language:", None)]
+    #[case("#xx\tyy:zz", None)]
+    #[case("# language: en", Some(("language", "en")))]
+    #[case("  #language:en-us \n", Some(("language", "en-us")))]
+    fn test_to_header_pair(
+        #[case] input: &str,
+        #[case] expected: Option<(&str, &str)>,
+    ) {
+        assert_eq!(expected, to_header_pair(input));
     }
 
     #[rstest]
@@ -144,4 +297,61 @@ mod tests {
         items.sort_by(|i, j| i.0.cmp(&j.0));
         assert_eq!(expected, items);
     }
+
+    #[rstest]
+    #[case("words.txt", Compression::None)]
+    #[case("words", Compression::None)]
+    #[case("words.tsv.gz", Compression::Gzip)]
+    #[case("dir.gz/words.zst", Compression::Zstd)]
+    #[case("words.zstd", Compression::Zstd)]
+    fn test_compression_from_path(
+        #[case] path: &str,
+        #[case] expected: Compression,
+    ) {
+        assert_eq!(expected, Compression::from_path(path));
+    }
+
+    #[rstest]
+    #[case(b"", Compression::None)]
+    #[case(b"\x1f", Compression::None)]
+    #[case(b"\x1f\x8b\x08", Compression::Gzip)]
+    #[case(b"\x28\xb5\x2f\xfd", Compression::Zstd)]
+    #[case(b"word\tw", Compression::None)]
+    fn test_compression_from_magic(
+        #[case] bytes: &[u8],
+        #[case] expected: Compression,
+    ) {
+        assert_eq!(expected, Compression::from_magic(bytes));
+    }
+
+    #[rstest]
+    #[case("words.txt", Compression::None)]
+    #[case("words.txt.gz", Compression::Gzip)]
+    #[case("words.txt.zst", Compression::Zstd)]
+    fn test_open_compressed_file(
+        #[case] fname: &str,
+        #[case] expected: Compression,
+    ) {
+        let dir = assert_fs::TempDir::new().unwrap();
+        let path = dir.path().join(fname);
+        let mut output = open_output_file(&Some(&path)).unwrap();
+        output.write_all("word\twɜːd\n".as_bytes()).unwrap();
+        output.finish().unwrap();
+
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(expected, Compression::from_magic(&bytes));
+        let mut res = String::new();
+        let mut input = open_input_file(&Some(&path)).unwrap();
+        input.read_to_string(&mut res).unwrap();
+        assert_eq!("word\twɜːd\n", res);
+        // magic bytes are used if the extension is unknown
+        let renamed = dir.path().join("words");
+        fs::rename(&path, &renamed).unwrap();
+        let mut res = String::new();
+        let mut input = open_input_file(&Some(&renamed)).unwrap();
+        input.read_to_string(&mut res).unwrap();
+        assert_eq!("word\twɜːd\n", res);
+
+        dir.close().unwrap();
+    }
 }
@@ -1,12 +1,13 @@
 use std::collections::hash_map::IntoValues;
 use std::collections::HashMap;
-use std::fmt::{self, Display};
+use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
 use std::slice;
+use std::str::FromStr;
 
 use union_find_rs::prelude::*;
 
-use crate::dictionary::Dictionary;
+use crate::dictionary::{Dictionary, Word};
 use crate::util::Multimap;
 
 #[derive(Debug, Clone)]
@@ -14,6 +15,25 @@ pub struct WordGroups {
     groups: Vec<Vec<String>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Grouping {
+    Connected,
+    Complete,
+    Star,
+    Diameter(f64),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceFilter {
+    pub min_sources: usize,
+    pub required: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGrouping {
+    grouping: String,
+}
+
 pub struct GroupBuilder<K, V>
 where
     K: Copy + Eq + Hash,
@@ -23,6 +43,52 @@ where
     sets: DisjointSets<K>,
 }
 
+impl Display for UnknownGrouping {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "Unknown grouping {:?}", self.grouping)
+    }
+}
+
+impl std::error::Error for UnknownGrouping {}
+
+impl FromStr for Grouping {
+    type Err = UnknownGrouping;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "connected" => Ok(Self::Connected),
+            None if s == "complete" => Ok(Self::Complete),
+            None if s == "star" => Ok(Self::Star),
+            Some(("diameter", d)) => match d.parse::<f64>() {
+                Ok(d) if d > 0.0 => Ok(Self::Diameter(d)),
+                _ => Err(UnknownGrouping {
+                    grouping: String::from(s),
+                }),
+            },
+            _ => Err(UnknownGrouping {
+                grouping: String::from(s),
+            }),
+        }
+    }
+}
+
+impl SourceFilter {
+    pub fn new(min_sources: usize) -> Self {
+        Self {
+            min_sources,
+            required: Vec::new(),
+        }
+    }
+
+    pub fn matches<I: IntoIterator<Item = usize>>(&self, sources: I) -> bool {
+        let mut sources: Vec<usize> = sources.into_iter().collect();
+        sources.sort_unstable();
+        sources.dedup();
+        sources.len() >= self.min_sources
+            && self.required.iter().all(|s| sources.contains(s))
+    }
+}
+
 impl WordGroups {
     pub fn new() -> Self {
         Self { groups: Vec::new() }
@@ -40,31 +106,54 @@ impl WordGroups {
         self.groups.iter()
     }
 
-    pub fn from_dict(dict: Dictionary) -> Self {
-        let mut hash = HashMap::<String, Vec<String>>::new();
-        for word in dict {
-            hash.mm_insert(word.phonemes, word.word);
-        }
-        hash.into_values().filter(|v| v.len() > 1).collect()
+    pub fn sort_by_key<K, F>(&mut self, f: F)
+    where
+        K: Ord,
+        F: FnMut(&Vec<String>) -> K,
+    {
+        self.groups.sort_by_key(f);
     }
 
-    pub fn from_dicts(dict: Dictionary, dict2: Dictionary) -> Self {
-        let mut hash = HashMap::<String, Vec<(String, usize)>>::new();
-        for (i, d) in [dict, dict2].into_iter().enumerate() {
-            for word in d {
-                hash.mm_insert(word.phonemes, (word.word, i));
-            }
-        }
-        hash.into_values()
-            .filter(|v| {
-                let l = v.len();
-                l > 1 && v[0].1 != v[l - 1].1
-            })
-            .map(|v| v.into_iter().map(|w| w.0))
+    pub fn from_dict(dict: &Dictionary) -> Self {
+        Self::from_dicts(&[dict], &SourceFilter::new(1))
+    }
+
+    pub fn from_dicts(dicts: &[&Dictionary], filter: &SourceFilter) -> Self {
+        group_words(dicts, filter)
+            .into_iter()
+            .map(|g| g.into_iter().map(|w| w.0.word.clone()))
             .collect()
     }
 }
 
+pub fn group_words<'a>(
+    dicts: &[&'a Dictionary],
+    filter: &SourceFilter,
+) -> Vec<Vec<(&'a Word, usize)>> {
+    let mut hash = HashMap::<&str, Vec<(&Word, usize)>>::new();
+    for (i, dict) in dicts.iter().enumerate() {
+        for word in dict.iter() {
+            hash.mm_insert(&word.phonemes, (word, i));
+        }
+    }
+    let mut res: Vec<Vec<(&Word, usize)>> = hash
+        .into_values()
+        .filter_map(|mut g| {
+            g.sort_by(|w, w1| w.0.word.cmp(&w1.0.word).then(w.1.cmp(&w1.1)));
+            g.dedup_by(|w, w1| w.0.word == w1.0.word && w.1 == w1.1);
+            let words = g.iter().any(|w| w.0.word != g[0].0.word);
+            let sources = filter.matches(g.iter().map(|w| w.1));
+            if words && sources {
+                Some(g)
+            } else {
+                None
+            }
+        })
+        .collect();
+    res.sort_by(|g, g1| g[0].0.word.cmp(&g1[0].0.word));
+    res
+}
+
 impl Default for WordGroups {
     fn default() -> Self {
         Self::new()
@@ -183,6 +272,21 @@ mod tests {
     use crate::dictionary::Dictionary;
     use rstest::*;
 
+    #[rstest]
+    #[case("connected", Ok(Grouping::Connected))]
+    #[case("complete", Ok(Grouping::Complete))]
+    #[case("star", Ok(Grouping::Star))]
+    #[case("diameter:1.5", Ok(Grouping::Diameter(1.5)))]
+    #[case("diameter:0", Err(UnknownGrouping { grouping: String::from("diameter:0") }))]
+    #[case("diameter", Err(UnknownGrouping { grouping: String::from("diameter") }))]
+    #[case("xx", Err(UnknownGrouping { grouping: String::from("xx") }))]
+    fn test_grouping_from_str(
+        #[case] input: &str,
+        #[case] expected: Result<Grouping, UnknownGrouping>,
+    ) {
+        assert_eq!(expected, input.parse());
+    }
+
     #[rstest]
     #[case(vec![], vec![])]
     #[case(vec![vec!["2", "1"]], vec![vec!["1", "2"]])]
@@ -201,13 +305,14 @@ mod tests {
     #[case(&[("w", "p"), ("w1", "p1")], vec![])]
     #[case(&[("w", "p"), ("w", "p")], vec![])]
     #[case(&[("c", "p"), ("a", "p"), ("d", "q")], vec![vec!["a", "c"]])]
+    #[case(&[("c", "pa"), ("a", "pa"), ("d", "ˈpa"), ("b", "p a")], vec![vec!["a", "c"]])]
     #[case(&[("c", "p"), ("b", "p"), ("d", "q"), ("a", "q")], vec![vec!["a", "d"], vec!["b", "c"]])]
     fn test_word_groups_from_dict(
         #[case] entries: &[(&str, &str)],
         #[case] expected: Vec<Vec<&str>>,
     ) {
         let dict = Dictionary::from_entries(entries);
-        assert_eq!(expected, WordGroups::from_dict(dict).groups);
+        assert_eq!(expected, WordGroups::from_dict(&dict).groups);
     }
 
     #[rstest]
@@ -224,7 +329,73 @@ mod tests {
     ) {
         let dict = Dictionary::from_entries(entries);
         let dict2 = Dictionary::from_entries(entries2);
-        assert_eq!(expected, WordGroups::from_dicts(dict, dict2).groups);
+        let filter = SourceFilter::new(2);
+        let res = WordGroups::from_dicts(&[&dict, &dict2], &filter);
+        assert_eq!(expected, res.groups);
+    }
+
+    #[rstest]
+    #[case(SourceFilter::new(1), vec![vec!["a", "b"], vec!["c", "d", "e"], vec!["f", "g"]])]
+    #[case(SourceFilter::new(2), vec![vec!["a", "b"], vec!["c", "d", "e"]])]
+    #[case(SourceFilter::new(3), vec![vec!["c", "d", "e"]])]
+    #[case(SourceFilter { min_sources: 1, required: vec![0] }, vec![vec!["a", "b"], vec!["c", "d", "e"], vec!["f", "g"]])]
+    #[case(SourceFilter { min_sources: 2, required: vec![2] }, vec![vec!["c", "d", "e"]])]
+    fn test_word_groups_from_dicts_filter(
+        #[case] filter: SourceFilter,
+        #[case] expected: Vec<Vec<&str>>,
+    ) {
+        let dicts = vec![
+            Dictionary::from_entries(&[
+                ("a", "p"),
+                ("c", "q"),
+                ("f", "r"),
+                ("g", "r"),
+            ]),
+            Dictionary::from_entries(&[("b", "p"), ("d", "q")]),
+            Dictionary::from_entries(&[("e", "q")]),
+        ];
+        let dicts: Vec<&Dictionary> = dicts.iter().collect();
+        assert_eq!(expected, WordGroups::from_dicts(&dicts, &filter).groups);
+    }
+
+    #[rstest]
+    #[case(SourceFilter::new(0), &[], true)]
+    #[case(SourceFilter::new(2), &[1, 1, 1], false)]
+    #[case(SourceFilter::new(2), &[1, 0, 1], true)]
+    #[case(SourceFilter { min_sources: 1, required: vec![2] }, &[0, 1], false)]
+    #[case(SourceFilter { min_sources: 1, required: vec![0, 2] }, &[2, 0], true)]
+    fn test_source_filter_matches(
+        #[case] filter: SourceFilter,
+        #[case] sources: &[usize],
+        #[case] expected: bool,
+    ) {
+        assert_eq!(expected, filter.matches(sources.iter().copied()));
+    }
+
+    #[rstest]
+    #[case(&[], &[], vec![])]
+    #[case(&[("c", "p"), ("a", "p"), ("d", "q")], &[], vec![vec![("a", 0), ("c", 0)]])]
+    #[case(&[("a", "p"), ("a", "ˈp")], &[], vec![])]
+    #[case(&[("a", "p"), ("b", "p")], &[("c", "q")], vec![])]
+    #[case(&[("w", "p")], &[("w", "p")], vec![])]
+    #[case(&[("a", "p"), ("c", "p")], &[("b", "p"), ("d", "q")], vec![vec![("a", 0), ("b", 1), ("c", 0)]])]
+    fn test_group_words(
+        #[case] entries: &[(&str, &str)],
+        #[case] entries2: &[(&str, &str)],
+        #[case] expected: Vec<Vec<(&str, usize)>>,
+    ) {
+        let dict = Dictionary::from_entries(entries);
+        let dict2 = Dictionary::from_entries(entries2);
+        let (dicts, filter) = if entries2.is_empty() {
+            (vec![&dict], SourceFilter::new(1))
+        } else {
+            (vec![&dict, &dict2], SourceFilter::new(2))
+        };
+        let res: Vec<Vec<(&str, usize)>> = group_words(&dicts, &filter)
+            .into_iter()
+            .map(|g| g.into_iter().map(|w| (&w.0.word[..], w.1)).collect())
+            .collect();
+        assert_eq!(expected, res);
     }
 
     #[rstest]
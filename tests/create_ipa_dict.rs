@@ -1,6 +1,7 @@
 use rstest::*;
 use std::fmt;
 use std::fs;
+use std::io::Read;
 //use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
 use predicates::prelude::*;
@@ -38,7 +39,8 @@ impl<'a> PredicateReflection for IsDictionaryPredicate<'a> {}
 
 impl<'a> Predicate<str> for IsDictionaryPredicate<'a> {
     fn eval(&self, variable: &str) -> bool {
-        for (i, line) in variable.lines().enumerate() {
+        let lines = variable.lines().filter(|l| !l.starts_with('#'));
+        for (i, line) in lines.enumerate() {
             let mut line = line.split('\t');
             let word = match line.next() {
                 Some(w) => w,
@@ -75,6 +77,18 @@ fn test_create_dict_stdio(#[case] input: &str, #[case] expected: &[&str]) {
         .stdout(is_dictionary(expected));
 }
 
+#[rstest]
+#[case(&["-l", "en"], "# language: en\n")]
+#[case(&["-v", "en"], "# language: en")]
+fn test_create_dict_language(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("test\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(expected));
+}
+
 #[rstest]
 #[case("test\nabc", &["test", "abc"])]
 fn test_create_dict_files(#[case] input: &str, #[case] expected: &[&str]) {
@@ -104,3 +118,34 @@ fn test_create_dict_files(#[case] input: &str, #[case] expected: &[&str]) {
 
     dir.close().unwrap();
 }
+
+#[rstest]
+#[case("output.txt.gz")]
+#[case("output.txt.zst")]
+fn test_create_dict_compressed(#[case] fname: &str) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let output_path = dir.child(fname);
+
+    cmd()
+        .args(&["-l", "en", "-o", output_path.to_str().unwrap()])
+        .write_stdin("test\nabc")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let file = fs::File::open(output_path.path()).unwrap();
+    let mut output = String::new();
+    if fname.ends_with(".gz") {
+        flate2::read::MultiGzDecoder::new(file)
+            .read_to_string(&mut output)
+            .unwrap();
+    } else {
+        zstd::Decoder::new(file)
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+    }
+    assert!(is_dictionary(&["test", "abc"]).eval(&output));
+
+    dir.close().unwrap();
+}
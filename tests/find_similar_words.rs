@@ -2,6 +2,7 @@ use rstest::*;
 //use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
 use predicates::prelude::*;
+use std::io::{Read, Write};
 
 fn cmd() -> assert_cmd::Command {
     assert_cmd::Command::cargo_bin("find-similar-words").unwrap()
@@ -53,6 +54,404 @@ fn test_distance(
         .stdout(predicate::eq(expected));
 }
 
+#[rstest]
+#[case("a\tx\n ab\txy\n bb\tyy\n", 1, "a ab\nab a bb\nbb ab\n")]
+#[case("a\tx\n ab\txy\n bb\tyy\n", 2, "a ab bb\nab a bb\nbb a ab\n")]
+fn test_index(
+    #[case] input: &str,
+    #[case] max_distance: usize,
+    #[case] expected: &str,
+    #[values("none", "bk-tree", "deletions")] index: &str,
+) {
+    cmd()
+        .args(&["-d", &max_distance.to_string(), "-i", index])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case("a\tx\n ab\txy\n bb\tyy\n", 1, "a ab\nab a bb\nbb ab\n")]
+#[case("a\tx\n ab\txy\n bb\tyy\n", 2, "a ab bb\nab a bb\nbb a ab\n")]
+fn test_jobs(
+    #[case] input: &str,
+    #[case] max_distance: usize,
+    #[case] expected: &str,
+    #[values("0", "1", "2", "8")] jobs: &str,
+) {
+    cmd()
+        .args(&["-d", &max_distance.to_string(), "-j", jobs])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(
+    "pat\tpat\n bat\tbat\n bad\tbad\n mat\tmat\n",
+    "0.25",
+    "pat bat\nbat pat bad\nbad bat\n"
+)]
+#[case(
+    "pat\tpat\n bat\tbat\n bad\tbad\n mat\tmat\n",
+    "0.5",
+    "pat bat bad\nbat pat bad mat\nbad pat bat\nmat bat\n"
+)]
+fn test_features(
+    #[case] input: &str,
+    #[case] max_distance: &str,
+    #[case] expected: &str,
+    #[values("none", "bk-tree", "deletions")] index: &str,
+) {
+    cmd()
+        .args(&["-m", "features", "-d", max_distance, "-i", index])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[test]
+fn test_deletion_index_fallback() {
+    cmd()
+        .args(&["-m", "features", "-d", "1", "-i", "deletions"])
+        .write_stdin("pat\tpat\nbat\tbat\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq("pat bat\nbat pat\n"))
+        .stderr(predicate::str::contains(
+            "Warning: deletion index does not support 8 edits, using bk-tree",
+        ));
+}
+
+#[rstest]
+#[case("-n", "b2 b3\n")]
+#[case("strict", "")]
+#[case("default", "b2 b3\n")]
+#[case("aggressive", "b1 b2 b3\n")]
+fn test_normalize_profile(#[case] profile: &str, #[case] expected: &str) {
+    let input = "b1\tpat\nb2\tbat\nb3\tbaːt\n";
+    let args = match profile {
+        "-n" => vec!["-n"],
+        _ => vec!["-N", profile],
+    };
+    cmd()
+        .args(&args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case("b1\tbɪt\nb2\tbiːt\n", "b1 b2\n")]
+#[case("# language: ru\nb1\tbɪt\nb2\tbiːt\n", "b1 b2\n")]
+#[case("# language: en\nb1\tbɪt\nb2\tbiːt\n", "")]
+#[case("# language: en-us\nb1\tbɪt\nb2\tbiːt\nb3\tbˈiːt\n", "b2 b3\n")]
+fn test_normalize_language(#[case] input: &str, #[case] expected: &str) {
+    cmd()
+        .arg("-n")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[test]
+fn test_normalize_profile_file() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let profile_path = dir.child("profile.txt");
+
+    profile_path.write_str("# voicing\np b\n").unwrap();
+
+    cmd()
+        .args(&[std::ffi::OsStr::new("-N"), profile_path.as_os_str()])
+        .write_stdin("b1\tpat\nb2\tbat\nb3\tbaːt\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq("b1 b2\n"));
+
+    cmd().args(&["-N", "xx"]).write_stdin("").assert().failure();
+
+    dir.close().unwrap();
+}
+
+#[rstest]
+#[case(&["-k", "1"], "a ab:1\nab a:1\nb a:1\nbb ab:1\n")]
+#[case(&["-k", "2"], "a ab:1 b:1\nab a:1 b:1\nb a:1 ab:1\nbb ab:1 b:1\n")]
+#[case(&["-k", "3", "-d", "1"], "a ab:1 b:1\nab a:1 b:1 bb:1\nb a:1 ab:1 bb:1\nbb ab:1 b:1\n")]
+#[case(&["-k", "1", "-m", "features"], "a b:0.25\nab bb:0.25\nb a:0.25\nbb ab:0.25\n")]
+fn test_top(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("a\tp\nab\tpb\nb\tb\nbb\tbb\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&["-d", "1"], "a c\nc a\n")]
+#[case(&["-d", "2", "-D"], "a b:2 c:1\nb a:2\nc a:1\n")]
+#[case(&["-d", "1", "-A"], "a\tc\t1\tp -a t\nc\ta\t1\tp +a t\n")]
+#[case(&["-d", "0.5", "-m", "features", "-A"], "a\tb\t0.5\tp>b a t>d\nb\ta\t0.5\tb>p a d>t\n")]
+#[case(&["-k", "1", "-A"], "a\tc\t1\tp -a t\nb\ta\t2\tb>p a d>t\nc\ta\t1\tp +a t\n")]
+fn test_output(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("a\tpat\nb\tbad\nc\tpt\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&["-O", "jsonl", "-d", "1"], "a\tpat\nb\tbad\nc\tpt\n", "{\"words\":[{\"word\":\"a\",\"phonemes\":\"pat\",\"source\":\"-\"},{\"word\":\"c\",\"phonemes\":\"pt\",\"source\":\"-\"}],\"distances\":[[0,1,1]]}\n{\"words\":[{\"word\":\"c\",\"phonemes\":\"pt\",\"source\":\"-\"},{\"word\":\"a\",\"phonemes\":\"pat\",\"source\":\"-\"}],\"distances\":[[0,1,1]]}\n")]
+#[case(&["-O", "jsonl", "-d", "1"], "a\tpat\na\tkat\nc\tkit\n", "{\"words\":[{\"word\":\"a\",\"phonemes\":\"kat\",\"source\":\"-\"},{\"word\":\"c\",\"phonemes\":\"kit\",\"source\":\"-\"}],\"distances\":[[0,1,1]]}\n{\"words\":[{\"word\":\"c\",\"phonemes\":\"kit\",\"source\":\"-\"},{\"word\":\"a\",\"phonemes\":\"kat\",\"source\":\"-\"}],\"distances\":[[0,1,1]]}\n")]
+#[case(&["-O", "jsonl"], "ice cream\tajs\nI scream\tajs\nb\tbad\n", "{\"words\":[{\"word\":\"I scream\",\"phonemes\":\"ajs\",\"source\":\"-\"},{\"word\":\"ice cream\",\"phonemes\":\"ajs\",\"source\":\"-\"}],\"distances\":[[0,1,0]]}\n")]
+fn test_output_jsonl(
+    #[case] args: &[&str],
+    #[case] input: &str,
+    #[case] expected: &str,
+) {
+    cmd()
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&["-O", "csv", "-d", "1"], "a\tpat\nb\tbad\nc\tpt\n", "word,phonemes,source,match,match_phonemes,match_source,distance\na,pat,-,c,pt,-,1\nc,pt,-,a,pat,-,1\n")]
+#[case(&["-O", "tsv", "-d", "1"], "a\tpat\nb\tbad\nc\tpt\n", "word\tphonemes\tsource\tmatch\tmatch_phonemes\tmatch_source\tdistance\na\tpat\t-\tc\tpt\t-\t1\nc\tpt\t-\ta\tpat\t-\t1\n")]
+#[case(&["-O", "csv"], "ice cream\tajs\n\"ice\", cream\tajs\nb\tbad\n", "word,phonemes,source,match,match_phonemes,match_source,distance\n\"\"\"ice\"\", cream\",ajs,-,ice cream,ajs,-,0\n")]
+fn test_output_pairs(
+    #[case] args: &[&str],
+    #[case] input: &str,
+    #[case] expected: &str,
+) {
+    cmd()
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&["-O", "dot", "-d", "1"], "graph similar_words {\n  \"a\";\n  \"c\";\n  \"a\" -- \"c\" [weight=1, label=\"1\"];\n}\n")]
+#[case(&["-O", "dot"], "graph similar_words {\n}\n")]
+fn test_output_graph(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("a\tpat\nb\tbad\nc\tpt\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&["-d", "1"], "cat bat\nbat cat bad\nbad bat bid\nbid bad\n")]
+#[case(&["-d", "1", "-g", "connected"], "bad bat bid cat\n")]
+#[case(&["-d", "1", "-g", "complete"], "bad bid\nbat cat\n")]
+#[case(&["-d", "1", "-g", "star"], "bad bat cat\n")]
+#[case(&["-g", "star"], "")]
+fn test_grouping(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("cat\tkat\nbat\tbat\nbad\tbad\nbid\tbid\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&[], "a c\n")]
+#[case(&["-d", "1"], "a b c\nb a\nc a\n")]
+#[case(&["-d", "1", "-A"], "a\tb\t1\tp>b æ t\na\tc\t0\tp a t\nb\ta\t1\tb>p æ t\nc\ta\t0\tp a t\n")]
+#[case(&["-k", "2"], "a c:0 b:1\nb a:1 c:2\nc a:0 b:2\n")]
+fn test_variants(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("a\tpat\na\tpæt\nb\tbæt\nc\tpat\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&[], "pat patt\n")]
+#[case(&["-d", "1"], "bat pat patt\npat bat patt\npatt bat pat\n")]
+#[case(&["-k", "1", "-A"], "word\tbat\t3\tw>b ɝ>æ d>t\nbat\tpat\t1\tb>p ɑ t\npat\tpatt\t0\tp ɑ t\npatt\tpat\t0\tp ɑ t\n")]
+fn test_cmudict(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(&["-f", "cmudict"])
+        .args(args)
+        .write_stdin(
+            ";;; comment\nWORD  W ER1 D\nBAT  B AE1 T\nBAT(2)  B AA1 T\nPAT  P AA1 T\nPATT  P AA1 T\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&[], "a\tp a\na\tp ə\nb\tp ə\nc\tb a\n", "a b\n")]
+#[case(&["-f", "wikipron"], "a\tp a\na\tp ə\nb\tp ə\nc\tb a\n", "a b\n")]
+#[case(&["-d", "1"], "a\tp a\na\tp ə\nb\tp ə\nc\tb a\n", "a b c\nb a\nc a\n")]
+#[case(&[], "a\t/pa/, /pə/\nb\t/pə/\nc\t/ba/\n", "a b\n")]
+#[case(&["-f", "ipa-dict"], "a\t/pa/, /pə/\nb\t/pə/\nc\t/ba/\n", "a b\n")]
+#[case(&["-f", "tsv"], "a\t/pa/, /pə/\nb\t/pə/\nc\t/ba/\n", "")]
+fn test_format(
+    #[case] args: &[&str],
+    #[case] input: &str,
+    #[case] expected: &str,
+) {
+    cmd()
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[test]
+fn test_lenient() {
+    cmd()
+        .write_stdin("a\tx\nbad\nb\tx\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq("a b\n"))
+        .stderr(predicate::str::contains(
+            "Warning: line 2: Could not parse line \"bad\"",
+        ));
+}
+
+#[test]
+fn test_strict() {
+    cmd()
+        .arg("-s")
+        .write_stdin("a\tx\nbad\nb\tx\n")
+        .assert()
+        .failure()
+        .stdout(predicate::str::is_empty())
+        .stderr(predicate::str::contains("line: 2"));
+}
+
+#[rstest]
+#[case(&[], "aa ab da\nba bb\n")]
+#[case(&["-F"], "ba bb\naa ab da\n")]
+#[case(&["--min-frequency", "2"], "ab da\nba bb\n")]
+#[case(&["-p", "noun"], "aa da\nba bb\n")]
+#[case(&["-F", "-k", "1", "-p", "NOUN"], "bb ba:0\nba bb:0\nda aa:0\naa da:0\nca aa:1\n")]
+fn test_metadata(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin(
+            "aa\ta\t1\tNOUN\nab\ta\t9\tVERB\nba\tb\t5\tNOUN\n\
+             bb\tb\t20\tNOUN\nca\tc\t\tNOUN\nda\ta\t2\tNOUN\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[test]
+fn test_cache() {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let input_path = dir.child("input.txt");
+    let cache_path = dir.child("cache.bin");
+
+    input_path
+        .write_str("aa\tab\nbb\tab\ncc\tac\nbad\n")
+        .unwrap();
+
+    let run = || {
+        cmd()
+            .args(&["-c", cache_path.to_str().unwrap(), "-d", "1"])
+            .args(&["-i", "deletions"])
+            .arg(input_path.as_os_str())
+            .assert()
+            .success()
+            .stdout(predicate::eq("aa bb cc\nbb aa cc\ncc aa bb\n"))
+    };
+    let warning = predicate::str::contains("Could not parse line \"bad\"");
+    run().stderr(warning.clone());
+    cache_path.assert(predicate::path::is_file());
+    run().stderr(warning);
+    cmd()
+        .args(&["-c", cache_path.to_str().unwrap(), "-d", "1", "-s"])
+        .args(&["-i", "deletions"])
+        .arg(input_path.as_os_str())
+        .assert()
+        .failure();
+
+    dir.close().unwrap();
+}
+
+#[rstest]
+#[case("input.tsv.gz")]
+#[case("input.tsv.zst")]
+fn test_compressed_file(#[case] fname: &str) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let input_path = dir.child(fname);
+    let input = "cc\ta\n bb\tb\n cc\ta\n aa\ta\n".as_bytes();
+    let compressed = if fname.ends_with(".gz") {
+        let mut res = flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::fast(),
+        );
+        res.write_all(input).unwrap();
+        res.finish().unwrap()
+    } else {
+        zstd::encode_all(input, 0).unwrap()
+    };
+
+    input_path.write_binary(&compressed).unwrap();
+
+    cmd()
+        .arg(input_path.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::eq("aa cc\n"));
+
+    dir.close().unwrap();
+}
+
+#[rstest]
+#[case("output.txt.gz")]
+#[case("output.txt.zst")]
+fn test_compressed_output(#[case] fname: &str) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    let output_path = dir.child(fname);
+
+    cmd()
+        .args(&["-o", output_path.to_str().unwrap()])
+        .write_stdin("cc\ta\n bb\tb\n cc\ta\n aa\ta\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    let file = std::fs::File::open(output_path.path()).unwrap();
+    let mut output = String::new();
+    if fname.ends_with(".gz") {
+        flate2::read::MultiGzDecoder::new(file)
+            .read_to_string(&mut output)
+            .unwrap();
+    } else {
+        zstd::Decoder::new(file)
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+    }
+    assert_eq!("aa cc\n", output);
+
+    dir.close().unwrap();
+}
+
 #[rstest]
 #[case("cc\ta\n bb\tb\n cc\ta\n aa\ta\n", "aa cc\n")]
 fn test_single_file(#[case] input: &str, #[case] expected: &str) {
@@ -94,3 +493,96 @@ fn test_multiple_files(
 
     dir.close().unwrap();
 }
+
+#[rstest]
+#[case(&[], "aa bb dd\ncc ee\n")]
+#[case(&["-S", "3"], "aa bb dd\n")]
+#[case(&["-R", "input.txt"], "aa bb dd\n")]
+#[case(&["-S", "3", "-k", "2"], "aa bb:0 dd:0\n")]
+fn test_source_filter(#[case] args: &[&str], #[case] expected: &str) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("input.txt").write_str("aa\ta\n").unwrap();
+    dir.child("input2.txt").write_str("bb\ta\ncc\tb\n").unwrap();
+    dir.child("input3.txt").write_str("dd\ta\nee\tb\n").unwrap();
+
+    cmd()
+        .current_dir(dir.path())
+        .args(args)
+        .args(&["input.txt", "input2.txt", "input3.txt"])
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+
+    dir.close().unwrap();
+}
+
+#[rstest]
+#[case(&[], "bad\tbat\tbat\tbat\t0\t1\ngift\tɡɪft\tGift\tɡɪft\t0\t0\n")]
+#[case(&["--min-spelling-distance", "1"], "bad\tbat\tbat\tbat\t0\t1\n")]
+#[case(&["-d", "1", "--min-spelling-distance", "2"], "bad\tbat\tbet\tbɛt\t1\t2\n")]
+#[case(&["-O", "csv", "--min-spelling-distance", "1"], "word,phonemes,source,match,match_phonemes,match_source,distance,spelling_distance\nbad,bat,input.txt,bat,bat,input2.txt,0,1\n")]
+fn test_false_friends(#[case] args: &[&str], #[case] expected: &str) {
+    let dir = assert_fs::TempDir::new().unwrap();
+    dir.child("input.txt")
+        .write_str("bad\tbat\ngift\tɡɪft\n")
+        .unwrap();
+    dir.child("input2.txt")
+        .write_str("bat\tbat\nbet\tbɛt\nGift\tɡɪft\n")
+        .unwrap();
+
+    cmd()
+        .current_dir(dir.path())
+        .arg("-x")
+        .args(args)
+        .args(&["input.txt", "input2.txt"])
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+
+    dir.close().unwrap();
+}
+
+#[rstest]
+#[case(&[], "bit sit\nmister sister\n")]
+#[case(&["-d", "1", "-g", "connected"], "bat bit sit\nmister sister\n")]
+fn test_rhymes(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .arg("-r")
+        .args(args)
+        .write_stdin(
+            "bit\tbˈɪt\nsit\tsˈɪt\nbat\tbˈæt\nmister\tmˈɪstɚ\nsister\tsˈɪstɚ\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&["-a"], "sip sit\nstrike strong\n")]
+#[case(&["-P", "onset"], "sip sit\nstrike strong\n")]
+#[case(&["-P", "stressed-syllable"], "below low\n")]
+#[case(&["-a", "-d", "1", "-g", "connected"], "below low sip sit\nstrike strong\n")]
+fn test_part(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin(
+            "strong\tstɹˈɒŋ\nstrike\tstɹˈaɪk\nsit\tsˈɪt\nsip\tsˈɪp\nbelow\tbəlˈoʊ\nlow\tlˈoʊ\nice\tˈaɪs\nowl\tˈaʊl\n",
+        )
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&[], "b~p\t2\tbat/pat bin/pin\nd~t\t1\tbad/bat\n")]
+#[case(&["--contrast", "t~d"], "d~t\t1\tbad/bat\n")]
+#[case(&["--contrast", "d~t", "-O", "csv"], "contrast,count,word,phonemes,match,match_phonemes,source\nd~t,1,bad,bad,bat,bat,-\n")]
+fn test_minimal_pairs(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .arg("-M")
+        .args(args)
+        .write_stdin("pat\tpat\nbat\tbat\nbad\tbad\npin\tpɪn\nbin\tbɪn\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
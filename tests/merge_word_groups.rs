@@ -1,4 +1,5 @@
 use rstest::*;
+use std::io::Write;
 //use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
 use predicates::prelude::*;
@@ -18,6 +19,37 @@ fn test_stdin(#[case] input: &str, #[case] expected: &str) {
         .stdout(predicate::eq(expected));
 }
 
+#[rstest]
+#[case(&["-O", "dot"], "a b:1 c:2\nb a:1\nd e\n", "graph similar_words {\n  \"a\";\n  \"b\";\n  \"c\";\n  \"d\";\n  \"e\";\n  \"a\" -- \"b\" [weight=1, label=\"1\"];\n  \"a\" -- \"c\" [weight=2, label=\"2\"];\n  \"d\" -- \"e\";\n}\n")]
+#[case(&["-O", "graphml"], "a b:0.5\n", "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  <key id=\"word\" for=\"node\" attr.name=\"word\" attr.type=\"string\"/>\n  <key id=\"distance\" for=\"edge\" attr.name=\"distance\" attr.type=\"double\"/>\n  <graph id=\"similar_words\" edgedefault=\"undirected\">\n    <node id=\"n0\"><data key=\"word\">a</data></node>\n    <node id=\"n1\"><data key=\"word\">b</data></node>\n    <edge source=\"n0\" target=\"n1\"><data key=\"distance\">0.5</data></edge>\n  </graph>\n</graphml>\n")]
+fn test_graph(
+    #[case] args: &[&str],
+    #[case] input: &str,
+    #[case] expected: &str,
+) {
+    cmd()
+        .args(args)
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
+#[rstest]
+#[case(&[], "bad bat bid cat\n")]
+#[case(&["-g", "connected"], "bad bat bid cat\n")]
+#[case(&["-g", "complete"], "bad bid\nbat cat\n")]
+#[case(&["-g", "star"], "bad bat cat\n")]
+#[case(&["-g", "diameter:2"], "bad bat cat\n")]
+fn test_grouping(#[case] args: &[&str], #[case] expected: &str) {
+    cmd()
+        .args(args)
+        .write_stdin("cat bat\nbat cat bad\nbad bat bid\nbid bad\n")
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+}
+
 #[rstest]
 #[case("", "")]
 #[case("aa bb cc\n bb b\n a c\n", "a c\naa b bb cc\n")]
@@ -61,3 +93,48 @@ fn test_multiple_files(
 
     dir.close().unwrap();
 }
+
+fn compress(compression: &str, input: &str) -> Vec<u8> {
+    match compression {
+        "gz" => {
+            let mut res = flate2::write::GzEncoder::new(
+                Vec::new(),
+                flate2::Compression::fast(),
+            );
+            res.write_all(input.as_bytes()).unwrap();
+            res.finish().unwrap()
+        },
+        "zst" => zstd::encode_all(input.as_bytes(), 0).unwrap(),
+        _ => Vec::from(input.as_bytes()),
+    }
+}
+
+#[rstest]
+#[case("input.txt.gz", "gz")]
+#[case("input.txt.zst", "zst")]
+#[case("input.txt", "gz")]
+#[case("input.txt", "zst")]
+fn test_compressed_file(#[case] fname: &str, #[case] compression: &str) {
+    let input = "aa bb cc\n bb b\n a c\n";
+    let expected = "a c\naa b bb cc\n";
+    let dir = assert_fs::TempDir::new().unwrap();
+    let input_path = dir.child(fname);
+
+    input_path
+        .write_binary(&compress(compression, input))
+        .unwrap();
+
+    cmd()
+        .arg(input_path.as_os_str())
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+
+    cmd()
+        .write_stdin(compress(compression, input))
+        .assert()
+        .success()
+        .stdout(predicate::eq(expected));
+
+    dir.close().unwrap();
+}